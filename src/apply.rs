@@ -0,0 +1,384 @@
+//! 構成情報の宣言的な適用。
+//!
+//! 望ましい構成（`ClusterConfig`）と稼働中のクラスタの構成との差分から、
+//! それを解消するための操作群（`Plan`）を組み立てて実行する。
+use futures::future;
+use futures::{stream, Future, Stream};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use crate::client::config::Client;
+use crate::entity::bucket::Bucket;
+use crate::entity::config::{ClusterConfig, ConfigEntity, ConfigEntityId};
+use crate::entity::device::{Device, DeviceId};
+use crate::export;
+use crate::{Error, Result};
+
+/// 望ましい構成をファイルから読み込む。
+///
+/// ファイルの形式は拡張子から決定される（`export::Format::from_path`参照）。
+pub fn load_desired_state<P: AsRef<Path>>(path: P) -> Result<ClusterConfig> {
+    track!(export::load(path))
+}
+
+/// 稼働中のクラスタから構成情報を取得する。
+pub fn fetch(client: &Client) -> impl Future<Item = ClusterConfig, Error = Error> {
+    let c = client.clone();
+    let servers = client.list_servers().and_then(move |summaries| {
+        future::join_all(summaries.into_iter().map(move |s| c.get_server(s.id)))
+    });
+    let c = client.clone();
+    let devices = client.list_devices().and_then(move |summaries| {
+        future::join_all(summaries.into_iter().map(move |d| c.get_device(d.id)))
+    });
+    let c = client.clone();
+    let buckets = client.list_buckets().and_then(move |summaries| {
+        future::join_all(summaries.into_iter().map(move |b| c.get_bucket(b.id)))
+    });
+    servers
+        .join3(devices, buckets)
+        .map(|(servers, devices, buckets)| ClusterConfig {
+            servers: servers.into_iter().flatten().collect(),
+            devices: devices.into_iter().flatten().collect(),
+            buckets: buckets.into_iter().flatten().collect(),
+        })
+}
+
+/// `current`を`desired`に一致させるための操作群を計算する。
+///
+/// 登録・更新はサーバ、デバイス、バケツの順に並び、
+/// 仮想デバイスは子デバイス群よりも後に登録される。
+/// `prune`が`true`の場合には、`desired`に含まれないエンティティの削除も行う。
+/// 削除は登録とは逆にバケツ、デバイス、サーバの順に並ぶ。
+///
+/// シーケンス番号のように登録時に自動で決定される値や、
+/// デバイスの稼働状態のように専用のRPCでのみ変更される値は、差分の対象外となる。
+pub fn plan(desired: &ClusterConfig, current: &ClusterConfig, prune: bool) -> Plan {
+    let mut actions = Vec::new();
+
+    let servers = current
+        .servers
+        .iter()
+        .map(|s| (&s.id, s))
+        .collect::<BTreeMap<_, _>>();
+    for server in &desired.servers {
+        let mut server = server.clone();
+        let action = if let Some(current) = servers.get(&server.id) {
+            server.seqno = current.seqno;
+            if server == **current {
+                continue;
+            }
+            Action::Update(ConfigEntity::Server(server))
+        } else {
+            Action::Create(ConfigEntity::Server(server))
+        };
+        actions.push(action);
+    }
+
+    let devices = current
+        .devices
+        .iter()
+        .map(|d| (d.id(), d))
+        .collect::<BTreeMap<_, _>>();
+    for device in sort_devices(&desired.devices) {
+        let mut device = device.clone();
+        let action = if let Some(current) = devices.get(device.id()) {
+            device.set_seqno(current.seqno());
+            device.set_state(current.state());
+            if device == **current {
+                continue;
+            }
+            Action::Update(ConfigEntity::Device(device))
+        } else {
+            Action::Create(ConfigEntity::Device(device))
+        };
+        actions.push(action);
+    }
+
+    let buckets = current
+        .buckets
+        .iter()
+        .map(|b| (b.id(), b))
+        .collect::<BTreeMap<_, _>>();
+    for bucket in &desired.buckets {
+        let mut bucket = bucket.clone();
+        let action = if let Some(current) = buckets.get(bucket.id()) {
+            normalize_bucket(&mut bucket, current);
+            if bucket == **current {
+                continue;
+            }
+            Action::Update(ConfigEntity::Bucket(bucket))
+        } else {
+            Action::Create(ConfigEntity::Bucket(bucket))
+        };
+        actions.push(action);
+    }
+
+    if prune {
+        let desired_buckets = desired
+            .buckets
+            .iter()
+            .map(|b| b.id())
+            .collect::<BTreeSet<_>>();
+        for bucket in &current.buckets {
+            if !desired_buckets.contains(bucket.id()) {
+                actions.push(Action::Delete(ConfigEntityId::Bucket(bucket.id().clone())));
+            }
+        }
+
+        let desired_devices = desired
+            .devices
+            .iter()
+            .map(|d| d.id())
+            .collect::<BTreeSet<_>>();
+        for device in sort_devices(&current.devices).into_iter().rev() {
+            if !desired_devices.contains(device.id()) {
+                actions.push(Action::Delete(ConfigEntityId::Device(device.id().clone())));
+            }
+        }
+
+        let desired_servers = desired
+            .servers
+            .iter()
+            .map(|s| &s.id)
+            .collect::<BTreeSet<_>>();
+        for server in &current.servers {
+            if !desired_servers.contains(&server.id) {
+                actions.push(Action::Delete(ConfigEntityId::Server(server.id.clone())));
+            }
+        }
+    }
+
+    Plan { actions }
+}
+
+/// 構成情報を適用するための操作群。
+#[derive(Debug, Default, Clone)]
+pub struct Plan {
+    /// 実行順に並んだ操作群。
+    pub actions: Vec<Action>,
+}
+impl Plan {
+    /// 実行すべき操作が存在しないかどうかを判定する。
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// 操作群を先頭から順番に実行する。
+    ///
+    /// いずれかの操作が失敗した時点で処理を中断する。
+    pub fn apply(self, client: &Client) -> impl Future<Item = (), Error = Error> {
+        let client = client.clone();
+        stream::iter_ok(self.actions).for_each(move |action| action.execute(&client))
+    }
+}
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for action in &self.actions {
+            writeln!(f, "{}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// 構成情報に対する一つの操作。
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// エンティティを新規に登録する。
+    Create(ConfigEntity),
+
+    /// 既存のエンティティを更新する。
+    Update(ConfigEntity),
+
+    /// エンティティを削除する。
+    Delete(ConfigEntityId),
+}
+impl Action {
+    /// 操作を実行する。
+    pub fn execute(self, client: &Client) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+        match self {
+            Action::Create(entity) | Action::Update(entity) => match entity {
+                ConfigEntity::Server(s) => Box::new(client.put_server(s).map(|_| ())),
+                ConfigEntity::Device(d) => Box::new(client.put_device(d).map(|_| ())),
+                ConfigEntity::Bucket(b) => Box::new(client.put_bucket(b).map(|_| ())),
+            },
+            Action::Delete(id) => match id {
+                ConfigEntityId::Server(id) => Box::new(client.delete_server(id).map(|_| ())),
+                ConfigEntityId::Device(id) => Box::new(client.delete_device(id).map(|_| ())),
+                ConfigEntityId::Bucket(id) => Box::new(client.delete_bucket(id).map(|_| ())),
+            },
+        }
+    }
+}
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Action::Create(ref entity) => write!(f, "+ {}", entity.to_id()),
+            Action::Update(ref entity) => write!(f, "~ {}", entity.to_id()),
+            Action::Delete(ref id) => write!(f, "- {}", id),
+        }
+    }
+}
+
+/// 物理デバイスを先頭に置き、仮想デバイスは子デバイス群よりも後になるように並べる。
+///
+/// 循環や未知の子デバイスを含むために順序が決まらない仮想デバイスは末尾に置かれる。
+fn sort_devices(devices: &[Device]) -> Vec<&Device> {
+    let known = devices.iter().map(|d| d.id()).collect::<BTreeSet<_>>();
+    let mut sorted = devices
+        .iter()
+        .filter(|d| !d.is_virtual())
+        .collect::<Vec<_>>();
+    let mut placed = sorted
+        .iter()
+        .map(|d| d.id())
+        .collect::<BTreeSet<&DeviceId>>();
+    let mut pending = devices
+        .iter()
+        .filter(|d| d.is_virtual())
+        .collect::<Vec<_>>();
+    loop {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|d| match **d {
+            Device::Virtual(ref v) => v
+                .children
+                .iter()
+                .all(|c| placed.contains(c) || !known.contains(c)),
+            _ => true,
+        });
+        if ready.is_empty() {
+            sorted.extend(rest);
+            break;
+        }
+        placed.extend(ready.iter().map(|d| d.id()));
+        sorted.extend(ready);
+        pending = rest;
+    }
+    sorted
+}
+
+/// 登録時に自動で決定される値を`current`に合わせる。
+fn normalize_bucket(bucket: &mut Bucket, current: &Bucket) {
+    bucket.set_seqno(current.seqno());
+    if bucket.segment_count() == 0 {
+        bucket.set_segment_count(current.segment_count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn desired() -> Value {
+        json!({
+            "servers": [{"id": "s1", "host": "127.0.0.1", "port": 3000}],
+            "devices": [
+                {"virtual": {"id": "root", "children": ["v1"]}},
+                {"virtual": {"id": "v1", "children": ["m1", "m2"]}},
+                {"memory": {"id": "m1", "server": "s1", "capacity": 100}},
+                {"memory": {"id": "m2", "server": "s1", "capacity": 100}}
+            ],
+            "buckets": [
+                {"replicated": {"id": "b", "device": "root", "tolerable_faults": 1}}
+            ]
+        })
+    }
+
+    fn config(value: Value) -> ClusterConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn actions(plan: &Plan) -> Vec<String> {
+        plan.actions.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn children_are_created_before_parents() {
+        let plan = plan(&config(desired()), &ClusterConfig::default(), false);
+        assert_eq!(
+            actions(&plan),
+            [
+                "+ server s1",
+                "+ device m1",
+                "+ device m2",
+                "+ device v1",
+                "+ device root",
+                "+ bucket b"
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_configs_yield_no_actions() {
+        // 登録時に決定される値や、専用のRPCでのみ変更される値は無視される
+        let mut current = desired();
+        current["servers"][0]["seqno"] = json!(3);
+        current["devices"][0]["virtual"]["seqno"] = json!(1);
+        current["devices"][2]["memory"]["seqno"] = json!(2);
+        current["devices"][2]["memory"]["state"] = json!("draining");
+        current["buckets"][0]["replicated"]["seqno"] = json!(5);
+        current["buckets"][0]["replicated"]["segment_count"] = json!(20);
+
+        let plan = plan(&config(desired()), &config(current), true);
+        assert!(plan.is_empty(), "{}", plan);
+    }
+
+    #[test]
+    fn different_entities_are_updated() {
+        let current = desired();
+        let mut desired = desired();
+        desired["servers"][0]["port"] = json!(3001);
+        desired["devices"][3]["memory"]["capacity"] = json!(200);
+        desired["buckets"][0]["replicated"]["segment_count"] = json!(10);
+
+        let plan = plan(&config(desired), &config(current), true);
+        assert_eq!(actions(&plan), ["~ server s1", "~ device m2", "~ bucket b"]);
+    }
+
+    #[test]
+    fn deletes_are_planned_only_when_pruning() {
+        let mut current = desired();
+        current["servers"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"id": "s2", "host": "127.0.0.1", "port": 3001}));
+        let devices = current["devices"].as_array_mut().unwrap();
+        devices.push(json!({"memory": {"id": "x1", "server": "s2", "capacity": 100}}));
+        devices.push(json!({"virtual": {"id": "x0", "children": ["x1"]}}));
+        current["buckets"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"metadata": {"id": "c", "device": "x0", "tolerable_faults": 0}}));
+        let current = config(current);
+
+        assert!(plan(&config(desired()), &current, false).is_empty());
+
+        // 削除は登録とは逆順（仮想デバイスは子デバイス群よりも先）に行われる
+        let plan = plan(&config(desired()), &current, true);
+        assert_eq!(
+            actions(&plan),
+            ["- bucket c", "- device x0", "- device x1", "- server s2"]
+        );
+    }
+
+    #[test]
+    fn sort_devices_works() {
+        let devices: Vec<Device> = serde_json::from_value(json!([
+            {"virtual": {"id": "a", "children": ["b"]}},
+            {"virtual": {"id": "b", "children": ["a"]}},
+            {"virtual": {"id": "c", "children": ["d", "unknown"]}},
+            {"virtual": {"id": "d", "children": ["m"]}},
+            {"memory": {"id": "m", "server": "s", "capacity": 1}}
+        ]))
+        .unwrap();
+
+        // 循環を含む仮想デバイスは末尾に置かれ、未知の子デバイスは無視される
+        let ids = sort_devices(&devices)
+            .into_iter()
+            .map(|d| d.id().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["m", "d", "c", "a", "b"]);
+    }
+}
@@ -0,0 +1,101 @@
+//! 望ましい構成を記述したJSONないしTOMLファイルをfrugalosクラスタに適用するコマンド。
+//!
+//! ```text
+//! USAGE: frugalos-apply [--dry-run] [--prune] [--actor <NAME>] <CONTACT_SERVER> <FILE>
+//! ```
+//!
+//! ファイルの形式は拡張子から決定される（`.toml`ならTOML、それ以外はJSON）。
+//! ファイルの内容が`entity::validation::validate_config`による検証に失敗した場合には、
+//! 見つかった問題を標準エラー出力に表示して終了する。
+//! ただし、警告（`Severity::Warning`）のみの場合には表示した上で処理を続行する。
+//! 適用前に実行予定の操作群を標準出力に表示する。
+//! `--dry-run`が指定された場合には表示のみを行い、クラスタは変更しない。
+//! `--prune`が指定された場合には、ファイルに含まれないエンティティをクラスタから削除する。
+//! `--actor`で指定された名前は、変更の主体として監査記録に残される。
+extern crate fibers;
+extern crate fibers_rpc;
+extern crate futures;
+extern crate libfrugalos;
+#[macro_use]
+extern crate trackable;
+
+use fibers::{Executor, InPlaceExecutor, Spawn};
+use fibers_rpc::client::ClientServiceBuilder;
+use futures::Future;
+use libfrugalos::apply;
+use libfrugalos::client::config::Client;
+use libfrugalos::entity::validation;
+use libfrugalos::Error;
+use std::net::SocketAddr;
+use std::process;
+
+const USAGE: &str =
+    "USAGE: frugalos-apply [--dry-run] [--prune] [--actor <NAME>] <CONTACT_SERVER> <FILE>";
+
+fn main() {
+    let mut dry_run = false;
+    let mut prune = false;
+    let mut actor = None;
+    let mut positionals = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--prune" => prune = true,
+            "--actor" => {
+                if let Some(name) = args.next() {
+                    actor = Some(name);
+                } else {
+                    eprintln!("{}", USAGE);
+                    process::exit(1);
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => positionals.push(arg),
+        }
+    }
+    if positionals.len() != 2 {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+    let contact_server: SocketAddr = match positionals[0].parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("Invalid contact server {:?}: {}", positionals[0], e);
+            process::exit(1);
+        }
+    };
+    let desired = track_try_unwrap!(apply::load_desired_state(&positionals[1]));
+    let problems = validation::validate_config(&desired);
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    if problems.iter().any(|p| p.is_error()) {
+        process::exit(1);
+    }
+
+    let mut executor = track_try_unwrap!(InPlaceExecutor::new().map_err(Error::from));
+    let rpc_service = ClientServiceBuilder::new().finish(executor.handle());
+    let mut client = Client::new(contact_server, rpc_service.handle());
+    client.set_actor(actor);
+    executor.spawn(rpc_service.map_err(|e| panic!("{}", e)));
+
+    let current = track_try_unwrap!(executor
+        .run_future(apply::fetch(&client))
+        .expect("Unexpected executor error"));
+    let plan = apply::plan(&desired, &current, prune);
+    if plan.is_empty() {
+        println!("No changes.");
+        return;
+    }
+    print!("{}", plan);
+    if dry_run {
+        return;
+    }
+    track_try_unwrap!(executor
+        .run_future(plan.apply(&client))
+        .expect("Unexpected executor error"));
+}
@@ -0,0 +1,275 @@
+//! バケツの容量およびオーバヘッドの計算。
+//!
+//! `simulator::Simulator`によるセグメント割当結果を元に、
+//! バケツに保存可能なオブジェクトの総量を見積もる。
+//!
+//! 見積もりは、オブジェクトが全セグメントに均等に分散され、
+//! セグメント内では各ノードに均等に保存されるという仮定に基づく。
+//! Raftのログやメタデータのような、オブジェクトの内容以外が消費する容量は考慮されない。
+use std::collections::BTreeMap;
+
+use crate::entity::bucket::{Bucket, BucketId};
+use crate::entity::device::DeviceId;
+use crate::simulator::Simulator;
+use crate::Result;
+
+/// バケツの容量の見積もり結果。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityReport {
+    /// 対象バケツのID。
+    pub bucket: BucketId,
+
+    /// セグメント数。
+    pub segment_count: u16,
+
+    /// 一つのセグメントを構成するノード（デバイス）の数。
+    pub device_group_size: u8,
+
+    /// ストレージ増幅率。
+    ///
+    /// 1バイトのオブジェクトを保存する際に消費される物理容量（バイト単位）。
+    pub amplification: f64,
+
+    /// ノードが割り当てられた物理デバイス群の容量の合計（バイト単位）。
+    pub raw_capacity: u64,
+
+    /// いずれかのデバイスが一杯になるまでに保存可能なオブジェクトの総量（バイト単位）。
+    pub usable_capacity: u64,
+
+    /// ノードが割り当てられた各物理デバイスの見積もり結果。
+    pub devices: BTreeMap<DeviceId, DeviceCapacity>,
+}
+impl CapacityReport {
+    /// 平均サイズが`average_object_size`バイトのオブジェクトを、最大でいくつ保存可能かを返す。
+    pub fn max_object_count(&self, average_object_size: u64) -> u64 {
+        if average_object_size == 0 {
+            return 0;
+        }
+        self.usable_capacity / average_object_size
+    }
+
+    /// 最初に一杯になる物理デバイスのIDを返す。
+    pub fn bottleneck(&self) -> Option<&DeviceId> {
+        self.devices
+            .iter()
+            .min_by_key(|(_, d)| d.max_bucket_bytes)
+            .map(|(id, _)| id)
+    }
+}
+
+/// 物理デバイス単位の容量の見積もり結果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceCapacity {
+    /// デバイスの容量（バイト単位）。
+    pub capacity: u64,
+
+    /// デバイスに割り当てられたノードの数。
+    pub node_count: usize,
+
+    /// このデバイスが一杯になる時点での、バケツ全体のオブジェクトの総量（バイト単位）。
+    pub max_bucket_bytes: u64,
+}
+
+/// バケツのストレージ増幅率を返す。
+///
+/// - `MetadataBucket`: Raftクラスタの全ノードが内容を保持するため`tolerable_faults * 2 + 1`
+/// - `ReplicatedBucket`: 複製の数である`tolerable_faults + 1`
+/// - `DispersedBucket`: `fragment_count() / data_fragment_count`
+/// - `HybridBucket`: 複製とErasureCodingのうち、増幅率が大きい方（オブジェクトのサイズ分布に依らない上限値）
+pub fn amplification(bucket: &Bucket) -> f64 {
+    match *bucket {
+        Bucket::Metadata(ref b) => f64::from(b.tolerable_faults) * 2.0 + 1.0,
+        Bucket::Replicated(ref b) => f64::from(b.tolerable_faults) + 1.0,
+        Bucket::Dispersed(ref b) => {
+            f64::from(b.fragment_count()) / f64::from(b.data_fragment_count)
+        }
+        Bucket::Hybrid(ref b) => {
+            let replicated = f64::from(b.replicated_tolerable_faults) + 1.0;
+            let dispersed = f64::from(b.fragment_count()) / f64::from(b.data_fragment_count);
+            replicated.max(dispersed)
+        }
+    }
+}
+
+/// バケツの容量を見積もる。
+///
+/// セグメント割当は`simulator`を用いて計算されるため、
+/// デバイスの容量の上書き（`Simulator::set_capacity`）もそのまま反映される。
+pub fn calculate(simulator: &Simulator, bucket: &Bucket) -> Result<CapacityReport> {
+    let placement = track!(simulator.simulate(bucket))?;
+    let segment_count = placement.segments.len() as u16;
+    let device_group_size = bucket.device_group_size();
+    let amplification = amplification(bucket);
+
+    // 総量`T`のオブジェクトを保存すると、各ノードには`T * 増幅率 / (セグメント数 * ノード数)`が書き込まれる
+    let bytes_per_node = amplification / (f64::from(segment_count) * f64::from(device_group_size));
+
+    let mut devices = BTreeMap::new();
+    for device in placement.used_devices() {
+        let capacity = track!(simulator.capacity(device))?;
+        let node_count = placement.load(device);
+        let max_bucket_bytes = (capacity as f64 / (node_count as f64 * bytes_per_node)) as u64;
+        devices.insert(
+            device.clone(),
+            DeviceCapacity {
+                capacity,
+                node_count,
+                max_bucket_bytes,
+            },
+        );
+    }
+    let raw_capacity = devices.values().map(|d| d.capacity).sum();
+    let usable_capacity = devices
+        .values()
+        .map(|d| d.max_bucket_bytes)
+        .min()
+        .unwrap_or(0);
+    Ok(CapacityReport {
+        bucket: placement.bucket,
+        segment_count,
+        device_group_size,
+        amplification,
+        raw_capacity,
+        usable_capacity,
+        devices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::entity::config::ClusterConfig;
+
+    fn bucket(value: Value) -> Bucket {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn dispersed(tolerable_faults: u32, data_fragment_count: u32, scheme: Value) -> Bucket {
+        bucket(json!({"dispersed": {
+            "id": "b",
+            "device": "root",
+            "tolerable_faults": tolerable_faults,
+            "data_fragment_count": data_fragment_count,
+            "erasure_coding": {"scheme": scheme}
+        }}))
+    }
+
+    fn hybrid(replicated: u32, dispersed: u32, data_fragment_count: u32) -> Bucket {
+        bucket(json!({"hybrid": {
+            "id": "b",
+            "device": "root",
+            "size_threshold": 1024,
+            "replicated_tolerable_faults": replicated,
+            "dispersed_tolerable_faults": dispersed,
+            "data_fragment_count": data_fragment_count
+        }}))
+    }
+
+    fn replicated(tolerable_faults: u32, segment_count: u32) -> Bucket {
+        bucket(json!({"replicated": {
+            "id": "b",
+            "device": "root",
+            "segment_count": segment_count,
+            "tolerable_faults": tolerable_faults
+        }}))
+    }
+
+    fn memory(id: &str, capacity: u64, weight: Value) -> Value {
+        json!({"memory": {"id": id, "server": "s", "capacity": capacity, "weight": weight}})
+    }
+
+    fn config(leaves: Vec<Value>) -> ClusterConfig {
+        let children = leaves
+            .iter()
+            .map(|d| d["memory"]["id"].clone())
+            .collect::<Vec<_>>();
+        let mut devices =
+            vec![json!({"virtual": {"id": "root", "policy": "SCATTER", "children": children}})];
+        devices.extend(leaves);
+        serde_json::from_value(json!({ "devices": devices })).unwrap()
+    }
+
+    #[test]
+    fn amplification_works() {
+        let metadata =
+            bucket(json!({"metadata": {"id": "b", "device": "root", "tolerable_faults": 1}}));
+        assert_eq!(amplification(&metadata), 3.0);
+        assert_eq!(amplification(&replicated(2, 0)), 3.0);
+
+        // データ4、パリティ2
+        assert_eq!(amplification(&dispersed(2, 4, json!("reed_solomon"))), 1.5);
+
+        // ローカルパリティが`ceil(4 / local_group_size)`個加わる
+        let lrc = json!({"locally_repairable": {"local_group_size": 2}});
+        assert_eq!(amplification(&dispersed(2, 4, lrc)), 2.0);
+        let lrc = json!({"locally_repairable": {"local_group_size": 3}});
+        assert_eq!(amplification(&dispersed(2, 4, lrc)), 2.0);
+        let lrc = json!({"locally_repairable": {"local_group_size": 4}});
+        assert_eq!(amplification(&dispersed(2, 4, lrc)), 1.75);
+
+        // 複製とErasureCodingのうち大きい方
+        assert_eq!(amplification(&hybrid(1, 2, 4)), 2.0);
+        assert_eq!(amplification(&hybrid(0, 2, 4)), 1.5);
+    }
+
+    #[test]
+    fn calculate_works() {
+        let config = config(vec![
+            memory("m1", 1000, json!("auto")),
+            memory("m2", 1000, json!("auto")),
+            memory("m3", 400, json!({"absolute": 1000})),
+        ]);
+        let simulator = Simulator::new(&config);
+        let report = calculate(&simulator, &replicated(1, 2)).unwrap();
+        assert_eq!(report.segment_count, 2);
+        assert_eq!(report.device_group_size, 3);
+        assert_eq!(report.amplification, 2.0);
+        assert_eq!(report.raw_capacity, 2400);
+
+        // 各デバイスに2ノードずつ割り当てられ、各ノードにはバケツ全体の`2 / (2 * 3)`が書き込まれる
+        for d in report.devices.values() {
+            assert_eq!(d.node_count, 2);
+        }
+        assert_eq!(report.devices["m1"].max_bucket_bytes, 1500);
+        assert_eq!(report.devices["m3"].max_bucket_bytes, 600);
+        assert_eq!(report.usable_capacity, 600);
+        assert_eq!(report.bottleneck().map(|d| d.as_str()), Some("m3"));
+
+        assert_eq!(report.max_object_count(100), 6);
+        assert_eq!(report.max_object_count(7), 85);
+        assert_eq!(report.max_object_count(601), 0);
+        assert_eq!(report.max_object_count(0), 0);
+    }
+
+    #[test]
+    fn calculate_handles_zero_capacity_and_weight() {
+        let config = config(vec![
+            memory("m1", 1000, json!("auto")),
+            memory("m2", 1000, json!("auto")),
+            memory("m3", 0, json!({"absolute": 1000})),
+            memory("m4", 0, json!("auto")),
+            memory("m5", 1000, json!({"absolute": 0})),
+        ]);
+        let simulator = Simulator::new(&config);
+        let report = calculate(&simulator, &replicated(1, 2)).unwrap();
+
+        // 重みが0のデバイス（容量0の`Weight::Auto`を含む）には割り当てられない
+        assert_eq!(
+            report
+                .devices
+                .keys()
+                .map(|d| d.as_str())
+                .collect::<Vec<_>>(),
+            ["m1", "m2", "m3"]
+        );
+        assert_eq!(report.raw_capacity, 2000);
+
+        // 容量0のデバイスが割り当てられた場合には、何も保存できない
+        assert_eq!(report.devices["m3"].max_bucket_bytes, 0);
+        assert_eq!(report.usable_capacity, 0);
+        assert_eq!(report.bottleneck().map(|d| d.as_str()), Some("m3"));
+        assert_eq!(report.max_object_count(1), 0);
+    }
+}
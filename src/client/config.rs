@@ -1,21 +1,30 @@
 //! 構成管理系API用のRPCクライアント。
+use fibers::time::timer::{self, Timeout};
 use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
 use fibers_rpc::Call as RpcCall;
-use futures::{Async, Future, Poll};
+use futures::{Async, Future, Poll, Stream};
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use super::Response;
-use crate::entity::bucket::{Bucket, BucketId, BucketSummary};
-use crate::entity::device::{Device, DeviceId, DeviceSummary};
+use crate::entity::bucket::{Bucket, BucketId, BucketSummary, Redundancy};
+use crate::entity::config::{ActorId, AuditEntry, ConfigChanges, ConfigRevision};
+use crate::entity::device::{Device, DeviceId, DeviceState, DeviceSummary};
+use crate::entity::impact::{FailureScenario, ImpactReport};
+use crate::entity::job::{JobId, JobStatus};
+use crate::entity::label::LabelSelector;
 use crate::entity::server::{Server, ServerId, ServerSummary};
+use crate::entity::validation::Problem;
 use crate::schema::config;
 use crate::{Error, ErrorKind, Result};
+use trackable::error::ErrorKindExt;
 
 /// RPCクライアント。
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Client {
     contact_server: SocketAddr,
     rpc_service: RpcServiceHandle,
+    actor: Option<ActorId>,
 }
 impl Client {
     /// 新しい`Client`インスタンスを生成する。
@@ -23,85 +32,434 @@ impl Client {
         Client {
             contact_server,
             rpc_service,
+            actor: None,
         }
     }
 
-    /// `ListServersRpc`を実行する。
+    /// 構成変更要求に付与する主体を設定する。
+    ///
+    /// ここで設定した値は、以後の登録・削除RPCの監査記録に残される。
+    pub fn set_actor(&mut self, actor: Option<ActorId>) {
+        self.actor = actor;
+    }
+
+    /// 構成変更要求に付与する主体を返す。
+    pub fn actor(&self) -> Option<&ActorId> {
+        self.actor.as_ref()
+    }
+
+    /// `ListServersV2Rpc`を実行する。
     pub fn list_servers(&self) -> impl Future<Item = Vec<ServerSummary>, Error = Error> {
-        Call::<config::ListServersRpc, _>::new(self, ())
+        Call::<config::ListServersV2Rpc, _>::new(self, ())
+    }
+
+    /// `ListServersByLabelRpc`を実行する。
+    pub fn list_servers_by_label(
+        &self,
+        selector: LabelSelector,
+    ) -> impl Future<Item = Vec<ServerSummary>, Error = Error> {
+        Call::<config::ListServersByLabelRpc, _>::new(self, selector)
     }
 
-    /// `GetServerRpc`を実行する。
+    /// `GetServerV2Rpc`を実行する。
     pub fn get_server(
         &self,
         server: ServerId,
     ) -> impl Future<Item = Option<Server>, Error = Error> {
-        Call::<config::GetServerRpc, _>::new(self, server)
+        Call::<config::GetServerV2Rpc, _>::new(self, server)
     }
 
-    /// `PutServerRpc`を実行する。
+    /// `PutServerV2Rpc`を実行する。
     pub fn put_server(&self, server: Server) -> impl Future<Item = Server, Error = Error> {
-        Call::<config::PutServerRpc, _>::new(self, server)
+        Call::<config::PutServerV2Rpc, _>::new(self, self.put_request(server))
     }
 
-    /// `DeleteServerRpc`を実行する。
+    /// `DeleteServerV2Rpc`を実行する。
     pub fn delete_server(
         &self,
         server: ServerId,
     ) -> impl Future<Item = Option<Server>, Error = Error> {
-        Call::<config::DeleteServerRpc, _>::new(self, server)
+        Call::<config::DeleteServerV2Rpc, _>::new(self, self.delete_request(server, false))
     }
 
-    /// `ListDevicesRpc`を実行する。
+    /// `DeleteServerV2Rpc`を、サーバ上にデバイスが存在する場合でも削除を強行するように実行する。
+    pub fn force_delete_server(
+        &self,
+        server: ServerId,
+    ) -> impl Future<Item = Option<Server>, Error = Error> {
+        Call::<config::DeleteServerV2Rpc, _>::new(self, self.delete_request(server, true))
+    }
+
+    /// `DecommissionServerRpc`を実行する。
+    ///
+    /// ジョブの完了を待つには`follow_job`を使用する。
+    pub fn decommission_server(
+        &self,
+        server: ServerId,
+    ) -> impl Future<Item = JobStatus, Error = Error> {
+        let request = config::DecommissionServerRequest {
+            server,
+            actor: self.actor.clone(),
+        };
+        Call::<config::DecommissionServerRpc, _>::new(self, request)
+    }
+
+    /// `ListDevicesByServerRpc`を実行する。
+    pub fn list_devices_by_server(
+        &self,
+        server: ServerId,
+    ) -> impl Future<Item = Vec<DeviceSummary>, Error = Error> {
+        Call::<config::ListDevicesByServerRpc, _>::new(self, server)
+    }
+
+    /// `ValidateServerRpc`を実行する。
+    pub fn validate_server(
+        &self,
+        server: Server,
+    ) -> impl Future<Item = Vec<Problem>, Error = Error> {
+        Call::<config::ValidateServerRpc, _>::new(self, server)
+    }
+
+    /// `ListDevicesV2Rpc`を実行する。
     pub fn list_devices(&self) -> impl Future<Item = Vec<DeviceSummary>, Error = Error> {
-        Call::<config::ListDevicesRpc, _>::new(self, ())
+        Call::<config::ListDevicesV2Rpc, _>::new(self, ())
     }
 
-    /// `GetDeviceRpc`を実行する。
+    /// `ListDevicesByLabelRpc`を実行する。
+    pub fn list_devices_by_label(
+        &self,
+        selector: LabelSelector,
+    ) -> impl Future<Item = Vec<DeviceSummary>, Error = Error> {
+        Call::<config::ListDevicesByLabelRpc, _>::new(self, selector)
+    }
+
+    /// `GetDeviceV2Rpc`を実行する。
     pub fn get_device(
         &self,
         device: DeviceId,
     ) -> impl Future<Item = Option<Device>, Error = Error> {
-        Call::<config::GetDeviceRpc, _>::new(self, device)
+        Call::<config::GetDeviceV2Rpc, _>::new(self, device)
     }
 
-    /// `PutDeviceRpc`を実行する。
+    /// `PutDeviceV2Rpc`を実行する。
     pub fn put_device(&self, device: Device) -> impl Future<Item = Device, Error = Error> {
-        Call::<config::PutDeviceRpc, _>::new(self, device)
+        Call::<config::PutDeviceV2Rpc, _>::new(self, self.put_request(device))
     }
 
-    /// `DeleteDeviceRpc`を実行する。
+    /// `DeleteDeviceV2Rpc`を実行する。
     pub fn delete_device(
         &self,
         device: DeviceId,
     ) -> impl Future<Item = Option<Device>, Error = Error> {
-        Call::<config::DeleteDeviceRpc, _>::new(self, device)
+        Call::<config::DeleteDeviceV2Rpc, _>::new(self, self.delete_request(device, false))
+    }
+
+    /// `DeleteDeviceV2Rpc`を、デバイスに依存するバケツ等が存在する場合でも削除を強行するように実行する。
+    pub fn force_delete_device(
+        &self,
+        device: DeviceId,
+    ) -> impl Future<Item = Option<Device>, Error = Error> {
+        Call::<config::DeleteDeviceV2Rpc, _>::new(self, self.delete_request(device, true))
+    }
+
+    /// `ReplaceDeviceRpc`を実行する。
+    ///
+    /// ジョブの完了を待つには`follow_job`を使用する。
+    pub fn replace_device(
+        &self,
+        old: DeviceId,
+        new: Device,
+    ) -> impl Future<Item = JobStatus, Error = Error> {
+        let request = config::ReplaceDeviceRequest {
+            old,
+            new,
+            actor: self.actor.clone(),
+        };
+        Call::<config::ReplaceDeviceRpc, _>::new(self, request)
     }
 
-    /// `ListBucketsRpc`を実行する。
+    /// `ListBucketsByDeviceRpc`を実行する。
+    pub fn list_buckets_by_device(
+        &self,
+        device: DeviceId,
+    ) -> impl Future<Item = Vec<BucketSummary>, Error = Error> {
+        Call::<config::ListBucketsByDeviceRpc, _>::new(self, device)
+    }
+
+    /// `ValidateDeviceRpc`を実行する。
+    pub fn validate_device(
+        &self,
+        device: Device,
+    ) -> impl Future<Item = Vec<Problem>, Error = Error> {
+        Call::<config::ValidateDeviceRpc, _>::new(self, device)
+    }
+
+    /// `SetDeviceStateRpc`を実行する。
+    pub fn set_device_state(
+        &self,
+        device: DeviceId,
+        state: DeviceState,
+    ) -> impl Future<Item = Device, Error = Error> {
+        let request = config::SetDeviceStateRequest {
+            device,
+            state,
+            actor: self.actor.clone(),
+        };
+        Call::<config::SetDeviceStateRpc, _>::new(self, request)
+    }
+
+    /// `ListBucketsV2Rpc`を実行する。
     pub fn list_buckets(&self) -> impl Future<Item = Vec<BucketSummary>, Error = Error> {
-        Call::<config::ListBucketsRpc, _>::new(self, ())
+        Call::<config::ListBucketsV2Rpc, _>::new(self, ())
     }
 
-    /// `GetBucketRpc`を実行する。
+    /// `ListBucketsByLabelRpc`を実行する。
+    pub fn list_buckets_by_label(
+        &self,
+        selector: LabelSelector,
+    ) -> impl Future<Item = Vec<BucketSummary>, Error = Error> {
+        Call::<config::ListBucketsByLabelRpc, _>::new(self, selector)
+    }
+
+    /// `GetBucketV2Rpc`を実行する。
     pub fn get_bucket(
         &self,
         bucket: BucketId,
     ) -> impl Future<Item = Option<Bucket>, Error = Error> {
-        Call::<config::GetBucketRpc, _>::new(self, bucket)
+        Call::<config::GetBucketV2Rpc, _>::new(self, bucket)
     }
 
-    /// `PutBucketRpc`を実行する。
+    /// `PutBucketV2Rpc`を実行する。
     pub fn put_bucket(&self, bucket: Bucket) -> impl Future<Item = Bucket, Error = Error> {
-        Call::<config::PutBucketRpc, _>::new(self, bucket)
+        Call::<config::PutBucketV2Rpc, _>::new(self, self.put_request(bucket))
     }
 
-    /// `DeleteBucketRpc`を実行する。
+    /// `DeleteBucketV2Rpc`を実行する。
     pub fn delete_bucket(
         &self,
         bucket: BucketId,
     ) -> impl Future<Item = Option<Bucket>, Error = Error> {
-        Call::<config::DeleteBucketRpc, _>::new(self, bucket)
+        Call::<config::DeleteBucketV2Rpc, _>::new(self, self.delete_request(bucket, false))
+    }
+
+    /// `ListConfigHistoryRpc`を実行する。
+    pub fn list_history(
+        &self,
+        request: config::ConfigHistoryRequest,
+    ) -> impl Future<Item = Vec<AuditEntry>, Error = Error> {
+        Call::<config::ListConfigHistoryRpc, _>::new(self, request)
+    }
+
+    /// `GetJobStatusRpc`を実行する。
+    pub fn get_job_status(
+        &self,
+        job: JobId,
+    ) -> impl Future<Item = Option<JobStatus>, Error = Error> {
+        Call::<config::GetJobStatusRpc, _>::new(self, job)
+    }
+
+    /// `ListJobsRpc`を実行する。
+    pub fn list_jobs(&self) -> impl Future<Item = Vec<JobStatus>, Error = Error> {
+        Call::<config::ListJobsRpc, _>::new(self, ())
+    }
+
+    /// `interval`間隔で`GetJobStatusRpc`を実行し、ジョブの状態を通知する`Stream`を返す。
+    ///
+    /// ジョブが終了した状態を通知した時点で`Stream`も終了する。
+    pub fn follow_job(&self, job: JobId, interval: Duration) -> FollowJob {
+        FollowJob {
+            client: self.clone(),
+            future: Call::new(self, job.clone()),
+            job,
+            interval,
+            timeout: None,
+            is_finished: false,
+        }
+    }
+
+    /// `AnalyzeFailureImpactRpc`を実行する。
+    pub fn analyze_failure_impact(
+        &self,
+        scenario: FailureScenario,
+    ) -> impl Future<Item = ImpactReport, Error = Error> {
+        Call::<config::AnalyzeFailureImpactRpc, _>::new(self, scenario)
+    }
+
+    /// `ReshardBucketRpc`を実行する。
+    ///
+    /// ジョブの完了を待つには`follow_job`を使用する。
+    pub fn reshard_bucket(
+        &self,
+        bucket: BucketId,
+        segment_count: u16,
+    ) -> impl Future<Item = JobStatus, Error = Error> {
+        let request = config::ReshardBucketRequest {
+            bucket,
+            segment_count,
+            actor: self.actor.clone(),
+        };
+        Call::<config::ReshardBucketRpc, _>::new(self, request)
+    }
+
+    /// `ChangeBucketRedundancyRpc`を実行する。
+    ///
+    /// ジョブの完了を待つには`follow_job`を使用する。
+    pub fn change_bucket_redundancy(
+        &self,
+        bucket: BucketId,
+        redundancy: Redundancy,
+    ) -> impl Future<Item = JobStatus, Error = Error> {
+        let request = config::ChangeBucketRedundancyRequest {
+            bucket,
+            redundancy,
+            actor: self.actor.clone(),
+        };
+        Call::<config::ChangeBucketRedundancyRpc, _>::new(self, request)
+    }
+
+    /// `ValidateBucketRpc`を実行する。
+    pub fn validate_bucket(
+        &self,
+        bucket: Bucket,
+    ) -> impl Future<Item = Vec<Problem>, Error = Error> {
+        Call::<config::ValidateBucketRpc, _>::new(self, bucket)
+    }
+
+    /// `GetConfigRevisionRpc`を実行する。
+    pub fn get_revision(&self) -> impl Future<Item = ConfigRevision, Error = Error> {
+        Call::<config::GetConfigRevisionRpc, _>::new(self, ())
+    }
+
+    /// `WatchConfigRpc`を実行する。
+    pub fn watch(
+        &self,
+        revision: ConfigRevision,
+        timeout: Duration,
+    ) -> impl Future<Item = ConfigChanges, Error = Error> {
+        let request = config::WatchConfigRequest { revision, timeout };
+        Call::<config::WatchConfigRpc, _>::new(self, request)
+    }
+
+    /// `revision`より後に行われた構成変更を監視し続ける`Stream`を返す。
+    ///
+    /// 内部では`WatchConfigRpc`を繰り返し発行する。
+    /// `timeout`は一回のRPCで応答を保留する最大時間であり、
+    /// 変更が無いまま`timeout`が経過した場合には何も通知せずに再度RPCを発行する。
+    pub fn watch_changes(&self, revision: ConfigRevision, timeout: Duration) -> Watch {
+        Watch {
+            client: self.clone(),
+            revision,
+            timeout,
+            future: Call::new(self, config::WatchConfigRequest { revision, timeout }),
+        }
+    }
+
+    fn put_request<T>(&self, entity: T) -> config::PutRequest<T> {
+        config::PutRequest {
+            entity,
+            actor: self.actor.clone(),
+        }
+    }
+
+    fn delete_request<T>(&self, id: T, force: bool) -> config::DeleteRequest<T> {
+        config::DeleteRequest {
+            id,
+            force,
+            actor: self.actor.clone(),
+        }
+    }
+}
+
+/// 構成変更を監視するための`Stream`。
+///
+/// `Client::watch_changes`によって生成される。
+#[derive(Debug)]
+pub struct Watch {
+    client: Client,
+    revision: ConfigRevision,
+    timeout: Duration,
+    future: Call<config::WatchConfigRpc, ConfigChanges>,
+}
+impl Watch {
+    /// 最後に通知した変更群を適用した時点でのリビジョンを返す。
+    pub fn revision(&self) -> ConfigRevision {
+        self.revision
+    }
+}
+impl Stream for Watch {
+    type Item = ConfigChanges;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            let changes = match track!(self.future.poll())? {
+                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(changes) => changes,
+            };
+            if changes.revision > self.revision {
+                self.revision = changes.revision;
+            }
+
+            let request = config::WatchConfigRequest {
+                revision: self.revision,
+                timeout: self.timeout,
+            };
+            self.future = Call::new(&self.client, request);
+            if !changes.changes.is_empty() {
+                return Ok(Async::Ready(Some(changes)));
+            }
+        }
+    }
+}
+
+/// ジョブの状態を追跡するための`Stream`。
+///
+/// `Client::follow_job`によって生成される。
+#[derive(Debug)]
+pub struct FollowJob {
+    client: Client,
+    job: JobId,
+    interval: Duration,
+    future: Call<config::GetJobStatusRpc, Option<JobStatus>>,
+    timeout: Option<Timeout>,
+    is_finished: bool,
+}
+impl Stream for FollowJob {
+    type Item = JobStatus;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.is_finished {
+            return Ok(Async::Ready(None));
+        }
+        if let Some(mut timeout) = self.timeout.take() {
+            match track!(timeout
+                .poll()
+                .map_err(|e| Error::from(ErrorKind::Other.cause(e))))?
+            {
+                Async::NotReady => {
+                    self.timeout = Some(timeout);
+                    return Ok(Async::NotReady);
+                }
+                Async::Ready(()) => {
+                    self.future = Call::new(&self.client, self.job.clone());
+                }
+            }
+        }
+
+        let status = match track!(self.future.poll())? {
+            Async::NotReady => return Ok(Async::NotReady),
+            Async::Ready(status) => status,
+        };
+        let status = track_assert_some!(
+            status,
+            ErrorKind::InvalidInput,
+            "Unknown job: {:?}",
+            self.job
+        );
+        self.timeout = Some(timer::timeout(self.interval));
+        self.is_finished = status.is_finished();
+        Ok(Async::Ready(Some(status)))
     }
 }
 
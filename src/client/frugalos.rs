@@ -1,7 +1,7 @@
 //! Frugalosの公開API用のRPCクライアント。
 use fibers_rpc::client::ClientServiceHandle as RpcServiceHandle;
 use fibers_rpc::Call as RpcCall;
-use futures::Future;
+use futures::{future, Future};
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::ops::Range;
@@ -9,8 +9,8 @@ use std::time::Duration;
 
 use super::Response;
 use crate::consistency::ReadConsistency;
-use crate::entity::bucket::BucketId;
-use crate::entity::device::DeviceId;
+use crate::entity::bucket::{Bucket, BucketId, BucketUsage, SegmentUsage};
+use crate::entity::device::{DeviceId, DeviceUsage};
 use crate::entity::object::{
     DeleteObjectsByPrefixSummary, FragmentsSummary, ObjectId, ObjectPrefix, ObjectSummary,
     ObjectVersion,
@@ -18,7 +18,7 @@ use crate::entity::object::{
 use crate::expect::Expect;
 use crate::multiplicity::MultiplicityConfig;
 use crate::repair::RepairConfig;
-use crate::schema::frugalos;
+use crate::schema::frugalos::{self, ObjectOptions, PutObjectOptions};
 use crate::Error;
 
 /// RPCクライアント。
@@ -178,6 +178,124 @@ impl Client {
         Response(frugalos::DeleteObjectRpc::client(&self.rpc_service).call(self.server, request))
     }
 
+    /// `GetObjectV2Rpc`を実行する。
+    ///
+    /// `options`で省略された設定には、対象バケツの`BucketDefaults`の値が使用される。
+    pub fn get_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        expect: Expect,
+        options: ObjectOptions,
+    ) -> impl Future<Item = Option<(ObjectVersion, Vec<u8>)>, Error = Error> {
+        let request = frugalos::ObjectRequestV2 {
+            bucket_id,
+            object_id,
+            expect,
+            options,
+        };
+        Response(frugalos::GetObjectV2Rpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `HeadObjectV2Rpc`を実行する。
+    ///
+    /// `options`で省略された設定には、対象バケツの`BucketDefaults`の値が使用される。
+    pub fn head_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        expect: Expect,
+        options: ObjectOptions,
+        check_storage: bool,
+    ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
+        let request = frugalos::HeadObjectRequestV2 {
+            bucket_id,
+            object_id,
+            expect,
+            options,
+            check_storage,
+        };
+        Response(frugalos::HeadObjectV2Rpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `PutObjectV2Rpc`を実行する。
+    ///
+    /// `options`で省略された設定には、対象バケツの`BucketDefaults`の値が使用される。
+    pub fn put_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        content: Vec<u8>,
+        expect: Expect,
+        options: PutObjectOptions,
+    ) -> impl Future<Item = (ObjectVersion, bool), Error = Error> {
+        let request = frugalos::PutObjectRequestV2 {
+            bucket_id,
+            object_id,
+            content,
+            expect,
+            options,
+        };
+        Response(frugalos::PutObjectV2Rpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `DeleteObjectV2Rpc`を実行する。
+    ///
+    /// `options`で省略された設定には、対象バケツの`BucketDefaults`の値が使用される。
+    pub fn delete_object_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        expect: Expect,
+        options: ObjectOptions,
+    ) -> impl Future<Item = Option<ObjectVersion>, Error = Error> {
+        let request = frugalos::ObjectRequestV2 {
+            bucket_id,
+            object_id,
+            expect,
+            options,
+        };
+        Response(frugalos::DeleteObjectV2Rpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `ListObjectsV2Rpc`を実行する。
+    ///
+    /// `consistency`が`None`の場合には、対象バケツの`BucketDefaults::read_consistency`が使用される。
+    pub fn list_objects_with_options(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: Option<ReadConsistency>,
+    ) -> impl Future<Item = Vec<ObjectSummary>, Error = Error> {
+        let request = frugalos::ListObjectsRequestV2 {
+            bucket_id,
+            segment,
+            consistency,
+        };
+        Response(frugalos::ListObjectsV2Rpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `CountFragmentsV2Rpc`を実行する。
+    ///
+    /// `options`で省略された設定には、対象バケツの`BucketDefaults`の値が使用される。
+    pub fn count_fragments_with_options(
+        &self,
+        bucket_id: BucketId,
+        object_id: ObjectId,
+        expect: Expect,
+        options: ObjectOptions,
+    ) -> impl Future<Item = Option<FragmentsSummary>, Error = Error> {
+        let request = frugalos::ObjectRequestV2 {
+            bucket_id,
+            object_id,
+            expect,
+            options,
+        };
+        Response(
+            frugalos::CountFragmentsV2Rpc::client(&self.rpc_service).call(self.server, request),
+        )
+    }
+
     /// `DeleteObjectByVersionRpc`を実行する。
     pub fn delete_object_by_version(
         &self,
@@ -254,6 +372,65 @@ impl Client {
         )
     }
 
+    /// `GetDeviceUsageRpc`を実行する。
+    pub fn device_usage(
+        &self,
+        device_id: DeviceId,
+    ) -> impl Future<Item = DeviceUsage, Error = Error> {
+        Response(
+            frugalos::GetDeviceUsageRpc::client(&self.rpc_service).call(self.server, device_id),
+        )
+    }
+
+    /// `ListDeviceUsagesRpc`を実行する。
+    pub fn list_device_usages(&self) -> impl Future<Item = Vec<DeviceUsage>, Error = Error> {
+        Response(frugalos::ListDeviceUsagesRpc::client(&self.rpc_service).call(self.server, ()))
+    }
+
+    /// `GetBucketUsageRpc`を実行する。
+    pub fn bucket_usage(
+        &self,
+        bucket_id: BucketId,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = BucketUsage, Error = Error> {
+        let request = frugalos::BucketUsageRequest {
+            bucket_id,
+            consistency,
+        };
+        Response(frugalos::GetBucketUsageRpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// `GetSegmentUsageRpc`を実行する。
+    pub fn segment_usage(
+        &self,
+        bucket_id: BucketId,
+        segment: u16,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = SegmentUsage, Error = Error> {
+        let request = frugalos::SegmentUsageRequest {
+            bucket_id,
+            segment,
+            consistency,
+        };
+        Response(frugalos::GetSegmentUsageRpc::client(&self.rpc_service).call(self.server, request))
+    }
+
+    /// バケツの全セグメントに対して`GetSegmentUsageRpc`を実行し、その結果を合算する。
+    ///
+    /// `GetBucketUsageRpc`とは異なり、各セグメントへの問い合わせはクライアント側から並行して行われる。
+    pub fn aggregate_bucket_usage(
+        &self,
+        bucket: &Bucket,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = BucketUsage, Error = Error> {
+        let bucket_id = bucket.id().clone();
+        let quota = bucket.quota().clone();
+        let futures = (0..bucket.segment_count())
+            .map(|segment| self.segment_usage(bucket_id.clone(), segment, consistency.clone()))
+            .collect::<Vec<_>>();
+        future::join_all(futures).map(move |segments| BucketUsage::new(bucket_id, quota, segments))
+    }
+
     /// `StopRpc`を実行する。
     pub fn stop(&self) -> impl Future<Item = (), Error = Error> {
         Response(frugalos::StopRpc::client(&self.rpc_service).call(self.server, ()))
@@ -13,6 +13,7 @@ use crate::entity::object::{
 };
 use crate::expect::Expect;
 use crate::schema::mds;
+use crate::time::Seconds;
 use crate::{Error, ErrorKind, Result};
 
 /// RPCクライアント。
@@ -76,6 +77,18 @@ impl Client {
         Call::<mds::GetObjectCountRpc, _>::new(self, request)
     }
 
+    /// セグメントが保持しているオブジェクトの数と、その内容の合計サイズ（バイト単位）を返す.
+    pub fn object_usage(
+        &self,
+        consistency: ReadConsistency,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, (u64, u64)), Error = Error> {
+        let request = mds::ObjectCountRequest {
+            node_id: self.node.1.clone(),
+            consistency,
+        };
+        Call::<mds::GetObjectUsageRpc, _>::new(self, request)
+    }
+
     /// `GetObjectRpc`を実行する。
     pub fn get_object(
         &self,
@@ -127,6 +140,29 @@ impl Client {
         Call::<mds::PutObjectRpc, _>::new(self, request)
     }
 
+    /// `PutObjectV2Rpc`を実行する。
+    ///
+    /// `expires_at`が`None`の場合の挙動は`put_object`と同じとなる。
+    pub fn put_object_with_expiry(
+        &self,
+        id: ObjectId,
+        metadata: Vec<u8>,
+        expect: Expect,
+        put_content_timeout: Duration,
+        expires_at: Option<Seconds>,
+    ) -> impl Future<Item = (Option<RemoteNodeId>, (ObjectVersion, Option<ObjectVersion>)), Error = Error>
+    {
+        let request = mds::PutObjectRequestV2 {
+            node_id: self.node.1.clone(),
+            object_id: id,
+            metadata,
+            expect,
+            put_content_timeout,
+            expires_at,
+        };
+        Call::<mds::PutObjectV2Rpc, _>::new(self, request)
+    }
+
     /// `DeleteObjectRpc`を実行する。
     pub fn delete_object(
         &self,
@@ -223,6 +259,11 @@ impl SetNodeId for mds::PutObjectRequest {
         self.node_id = node_id;
     }
 }
+impl SetNodeId for mds::PutObjectRequestV2 {
+    fn set_node_id(&mut self, node_id: LocalNodeId) {
+        self.node_id = node_id;
+    }
+}
 
 #[derive(Debug)]
 struct Call<T: RpcCall, U> {
@@ -5,12 +5,13 @@
 /// 整合性指定により、MDS から参照されるオブジェクトが最新か否かに影響を与える。
 /// 強整合性は、常に最新のオブジェクトが参照できることを意味する。
 /// 弱整合性は、最新ではない、古くなったオブジェクトが参照される可能性があることを意味する。
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum ReadConsistency {
     /// オブジェクトを参照する際に MDS のリーダーノードを参照する。強整合性を保証する。
     ///
     /// 古いオブジェクトが返ってこないことが保証されるが、リーダーが決まるまでは結果が取得できない。
     /// デフォルト値。
+    #[default]
     Consistent,
     /// オブジェクトを参照する際に過半数の MDS ノードを参照する。強整合性を保証する。
     ///
@@ -27,9 +28,3 @@ pub enum ReadConsistency {
     /// オブジェクトが更新された場合に古いデータを返す可能性がある。
     Stale,
 }
-
-impl Default for ReadConsistency {
-    fn default() -> Self {
-        ReadConsistency::Consistent
-    }
-}
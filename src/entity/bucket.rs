@@ -1,7 +1,13 @@
 //! バケツ関連のエンティティ定義。
 use std::cmp;
+use std::time::Duration;
 
+use crate::consistency::ReadConsistency;
 use crate::entity::device::DeviceId;
+use crate::entity::label::Labels;
+use crate::entity::object::ObjectPrefix;
+use crate::multiplicity::MultiplicityConfig;
+use crate::{ErrorKind, Result};
 
 // FIXME: 構造体に置き換える
 /// バケツのID。
@@ -19,10 +25,20 @@ pub struct BucketSummary {
 
     /// バケツが使用しているデバイス。
     pub device: DeviceId,
+
+    /// ErasureCodingの設定。
+    ///
+    /// `DispersedBucket`および`HybridBucket`以外の場合には`None`となる。
+    #[serde(default)]
+    pub erasure_coding: Option<ErasureCoding>,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// バケツの種類。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum BucketKind {
     /// メタデータ用バケツ。
@@ -33,10 +49,13 @@ pub enum BucketKind {
 
     /// ErasureCodingによる冗長化を行うバケツ。
     Dispersed,
+
+    /// オブジェクトのサイズに応じて複製とErasureCodingを使い分けるバケツ。
+    Hybrid,
 }
 
 /// バケツ。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Bucket {
     /// メタデータ用バケツ。
@@ -47,6 +66,9 @@ pub enum Bucket {
 
     /// ErasureCodingによる冗長化を行うバケツ。
     Dispersed(DispersedBucket),
+
+    /// オブジェクトのサイズに応じて複製とErasureCodingを使い分けるバケツ。
+    Hybrid(HybridBucket),
 }
 // FIXME: デフォルト実装は無くす（今はserdeのために必要）
 impl Default for Bucket {
@@ -57,6 +79,10 @@ impl Default for Bucket {
             device: String::new(),
             segment_count: 0,
             tolerable_faults: 0,
+            quota: BucketQuota::default(),
+            defaults: BucketDefaults::default(),
+            lifecycle_rules: Vec::new(),
+            labels: Labels::new(),
         })
     }
 }
@@ -83,6 +109,7 @@ impl Bucket {
             Bucket::Metadata(ref mut b) => b.segment_count = u32::from(count),
             Bucket::Replicated(ref mut b) => b.segment_count = u32::from(count),
             Bucket::Dispersed(ref mut b) => b.segment_count = u32::from(count),
+            Bucket::Hybrid(ref mut b) => b.segment_count = u32::from(count),
         }
     }
 
@@ -93,6 +120,7 @@ impl Bucket {
             Bucket::Metadata(ref b) => b.segment_count as u16,
             Bucket::Replicated(ref b) => b.segment_count as u16,
             Bucket::Dispersed(ref b) => b.segment_count as u16,
+            Bucket::Hybrid(ref b) => b.segment_count as u16,
         }
     }
 
@@ -104,7 +132,12 @@ impl Bucket {
             Bucket::Replicated(ref b) => (b.tolerable_faults * 2 + 1) as u8,
             Bucket::Dispersed(ref b) => {
                 let raft_cluster_size = (b.tolerable_faults * 2 + 1) as u8;
-                let ec_fragment_count = (b.tolerable_faults + b.data_fragment_count) as u8;
+                let ec_fragment_count = b.fragment_count() as u8;
+                cmp::max(raft_cluster_size, ec_fragment_count)
+            }
+            Bucket::Hybrid(ref b) => {
+                let raft_cluster_size = (b.raft_tolerable_faults() * 2 + 1) as u8;
+                let ec_fragment_count = b.fragment_count() as u8;
                 cmp::max(raft_cluster_size, ec_fragment_count)
             }
         }
@@ -116,6 +149,12 @@ impl Bucket {
             id: self.id().to_owned(),
             kind: self.kind(),
             device: self.device().to_owned(),
+            erasure_coding: match *self {
+                Bucket::Dispersed(ref b) => Some(b.erasure_coding.clone()),
+                Bucket::Hybrid(ref b) => Some(b.erasure_coding.clone()),
+                _ => None,
+            },
+            labels: self.labels().clone(),
         }
     }
 
@@ -125,6 +164,7 @@ impl Bucket {
             Bucket::Metadata(_) => BucketKind::Metadata,
             Bucket::Replicated(_) => BucketKind::Replicated,
             Bucket::Dispersed(_) => BucketKind::Dispersed,
+            Bucket::Hybrid(_) => BucketKind::Hybrid,
         }
     }
 
@@ -134,6 +174,199 @@ impl Bucket {
             Bucket::Metadata(ref b) => &b.device,
             Bucket::Replicated(ref b) => &b.device,
             Bucket::Dispersed(ref b) => &b.device,
+            Bucket::Hybrid(ref b) => &b.device,
+        }
+    }
+
+    /// バケツの冗長化方式を返す。
+    ///
+    /// `MetadataBucket`の場合には`None`となる。
+    pub fn redundancy(&self) -> Option<Redundancy> {
+        match *self {
+            Bucket::Metadata(_) => None,
+            Bucket::Replicated(ref b) => Some(Redundancy::Replicated {
+                tolerable_faults: b.tolerable_faults,
+            }),
+            Bucket::Dispersed(ref b) => Some(Redundancy::Dispersed {
+                tolerable_faults: b.tolerable_faults,
+                data_fragment_count: b.data_fragment_count,
+                erasure_coding: b.erasure_coding.clone(),
+            }),
+            Bucket::Hybrid(ref b) => Some(Redundancy::Hybrid {
+                replicated_tolerable_faults: b.replicated_tolerable_faults,
+                dispersed_tolerable_faults: b.dispersed_tolerable_faults,
+                data_fragment_count: b.data_fragment_count,
+                erasure_coding: b.erasure_coding.clone(),
+            }),
+        }
+    }
+
+    /// バケツの冗長化方式を変更する。
+    ///
+    /// 必要に応じてバケツの種類も変更される。
+    /// その場合でも、ID・デバイス・セグメント数・使用量の上限等の冗長化方式に関係しない設定は引き継がれる。
+    ///
+    /// # Errors
+    ///
+    /// `MetadataBucket`に対して呼ばれた場合や、
+    /// `HybridBucket`以外のバケツに`Redundancy::Hybrid`が指定された場合（`size_threshold`が定まらないため）には、
+    /// `ErrorKind::InvalidInput`が返される。
+    pub fn set_redundancy(&mut self, redundancy: Redundancy) -> Result<()> {
+        let size_threshold = match *self {
+            Bucket::Hybrid(ref b) => Some(b.size_threshold),
+            _ => None,
+        };
+        let (id, seqno, device, segment_count, quota, defaults, lifecycle_rules, labels) =
+            match self.clone() {
+                Bucket::Replicated(b) => (
+                    b.id,
+                    b.seqno,
+                    b.device,
+                    b.segment_count,
+                    b.quota,
+                    b.defaults,
+                    b.lifecycle_rules,
+                    b.labels,
+                ),
+                Bucket::Dispersed(b) => (
+                    b.id,
+                    b.seqno,
+                    b.device,
+                    b.segment_count,
+                    b.quota,
+                    b.defaults,
+                    b.lifecycle_rules,
+                    b.labels,
+                ),
+                Bucket::Hybrid(b) => (
+                    b.id,
+                    b.seqno,
+                    b.device,
+                    b.segment_count,
+                    b.quota,
+                    b.defaults,
+                    b.lifecycle_rules,
+                    b.labels,
+                ),
+                Bucket::Metadata(_) => track_panic!(
+                    ErrorKind::InvalidInput,
+                    "Cannot change the redundancy of a {:?} bucket: {:?}",
+                    self.kind(),
+                    self.id()
+                ),
+            };
+        *self = match redundancy {
+            Redundancy::Replicated { tolerable_faults } => Bucket::Replicated(ReplicatedBucket {
+                id,
+                seqno,
+                device,
+                segment_count,
+                tolerable_faults,
+                quota,
+                defaults,
+                lifecycle_rules,
+                labels,
+            }),
+            Redundancy::Dispersed {
+                tolerable_faults,
+                data_fragment_count,
+                erasure_coding,
+            } => Bucket::Dispersed(DispersedBucket {
+                id,
+                seqno,
+                device,
+                segment_count,
+                tolerable_faults,
+                data_fragment_count,
+                erasure_coding,
+                quota,
+                defaults,
+                lifecycle_rules,
+                labels,
+            }),
+            Redundancy::Hybrid {
+                replicated_tolerable_faults,
+                dispersed_tolerable_faults,
+                data_fragment_count,
+                erasure_coding,
+            } => {
+                let size_threshold = track_assert_some!(
+                    size_threshold,
+                    ErrorKind::InvalidInput,
+                    "Cannot change a {:?} bucket into a hybrid bucket: {:?}",
+                    self.kind(),
+                    id
+                );
+                Bucket::Hybrid(HybridBucket {
+                    id,
+                    seqno,
+                    device,
+                    segment_count,
+                    size_threshold,
+                    replicated_tolerable_faults,
+                    dispersed_tolerable_faults,
+                    data_fragment_count,
+                    erasure_coding,
+                    quota,
+                    defaults,
+                    lifecycle_rules,
+                    labels,
+                })
+            }
+        };
+        Ok(())
+    }
+
+    /// バケツの使用量の上限を返す。
+    pub fn quota(&self) -> &BucketQuota {
+        match *self {
+            Bucket::Metadata(ref b) => &b.quota,
+            Bucket::Replicated(ref b) => &b.quota,
+            Bucket::Dispersed(ref b) => &b.quota,
+            Bucket::Hybrid(ref b) => &b.quota,
+        }
+    }
+
+    /// バケツの要求設定の既定値を返す。
+    pub fn defaults(&self) -> &BucketDefaults {
+        match *self {
+            Bucket::Metadata(ref b) => &b.defaults,
+            Bucket::Replicated(ref b) => &b.defaults,
+            Bucket::Dispersed(ref b) => &b.defaults,
+            Bucket::Hybrid(ref b) => &b.defaults,
+        }
+    }
+
+    /// バケツのライフサイクルルール群を返す。
+    pub fn lifecycle_rules(&self) -> &[LifecycleRule] {
+        match *self {
+            Bucket::Metadata(ref b) => &b.lifecycle_rules,
+            Bucket::Replicated(ref b) => &b.lifecycle_rules,
+            Bucket::Dispersed(ref b) => &b.lifecycle_rules,
+            Bucket::Hybrid(ref b) => &b.lifecycle_rules,
+        }
+    }
+
+    /// ライフサイクルルール群に従って、指定オブジェクトの保存後の有効期間を返す。
+    ///
+    /// 複数のルールに該当する場合には、接頭辞が最も長いルールが適用される（S3と同様）。
+    /// 同じ長さの接頭辞を持つルールが複数ある場合には、それらの中で最も短い期間が採用される。
+    /// どのルールにも該当しない場合には`None`が返される。
+    pub fn expiration(&self, object_id: &str) -> Option<Duration> {
+        self.lifecycle_rules()
+            .iter()
+            .filter(|r| r.matches(object_id))
+            .max_by_key(|r| (r.prefix.0.len(), cmp::Reverse(r.expiration_days)))
+            .map(LifecycleRule::expiration)
+    }
+
+    /// バケツのラベル群を返す。
+    pub fn labels(&self) -> &Labels {
+        match *self {
+            Bucket::Metadata(ref b) => &b.labels,
+            Bucket::Replicated(ref b) => &b.labels,
+            Bucket::Dispersed(ref b) => &b.labels,
+            Bucket::Hybrid(ref b) => &b.labels,
         }
     }
 
@@ -143,6 +376,7 @@ impl Bucket {
             Bucket::Metadata(ref b) => &b.id,
             Bucket::Replicated(ref b) => &b.id,
             Bucket::Dispersed(ref b) => &b.id,
+            Bucket::Hybrid(ref b) => &b.id,
         }
     }
 
@@ -152,6 +386,7 @@ impl Bucket {
             Bucket::Metadata(ref mut b) => b.seqno = seqno,
             Bucket::Replicated(ref mut b) => b.seqno = seqno,
             Bucket::Dispersed(ref mut b) => b.seqno = seqno,
+            Bucket::Hybrid(ref mut b) => b.seqno = seqno,
         }
     }
 
@@ -161,15 +396,140 @@ impl Bucket {
             Bucket::Metadata(ref b) => b.seqno,
             Bucket::Replicated(ref b) => b.seqno,
             Bucket::Dispersed(ref b) => b.seqno,
+            Bucket::Hybrid(ref b) => b.seqno,
         }
     }
 }
 
+/// バケツの使用量の上限。
+///
+/// 上限を超えるオブジェクトの保存（`PutObjectRpc`）は`ErrorKind::QuotaExceeded`エラーとなる。
+/// 各項目が`None`の場合には無制限となる。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BucketQuota {
+    /// 保存可能なオブジェクトの内容の合計サイズ（バイト単位）の上限。
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+
+    /// 保存可能なオブジェクト数の上限。
+    #[serde(default)]
+    pub max_objects: Option<u64>,
+}
+impl BucketQuota {
+    /// 上限が一つも設定されていないかどうかを判定する。
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_objects.is_none()
+    }
+
+    /// 指定のオブジェクト数および合計サイズが上限を超えているかどうかを判定する。
+    pub fn is_exceeded(&self, object_count: u64, total_bytes: u64) -> bool {
+        self.max_objects.is_some_and(|max| object_count > max)
+            || self.max_bytes.is_some_and(|max| total_bytes > max)
+    }
+}
+
+/// バケツに対する要求の設定の既定値。
+///
+/// `schema::frugalos::GetObjectV2Rpc`等の要求で値が省略された場合には、サーバ側でこれらの値が適用される。
+/// ここでも`None`の場合には、frugalosの既定値が使用される。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketDefaults {
+    /// オブジェクト保存時の多重度の設定。
+    #[serde(default)]
+    pub multiplicity: Option<MultiplicityConfig>,
+
+    /// オブジェクト読み込み時の一貫性の保証レベル。
+    #[serde(default)]
+    pub read_consistency: Option<ReadConsistency>,
+
+    /// オブジェクト単位の要求のデッドライン。
+    #[serde(default)]
+    pub deadline: Option<Duration>,
+}
+
+/// オブジェクトの有効期限に関するルール。
+///
+/// IDが`prefix`で始まるオブジェクトは、保存されてから`expiration_days`日が経過した後に、
+/// frugalosのバックグラウンド処理によって削除される。
+/// `schema::frugalos::PutObjectOptions::ttl`が指定されたオブジェクトには、そちらが優先して適用される。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LifecycleRule {
+    /// 対象となるオブジェクトのIDの接頭辞。
+    #[serde(default)]
+    pub prefix: ObjectPrefix,
+
+    /// オブジェクトの保存から削除までの日数。
+    pub expiration_days: u32,
+}
+impl LifecycleRule {
+    /// 指定オブジェクトがこのルールの対象かどうかを判定する。
+    pub fn matches(&self, object_id: &str) -> bool {
+        object_id.starts_with(&self.prefix.0)
+    }
+
+    /// オブジェクトの保存から削除までの期間を返す。
+    pub fn expiration(&self) -> Duration {
+        Duration::from_secs(u64::from(self.expiration_days) * 24 * 60 * 60)
+    }
+}
+
+/// バケツの使用量。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketUsage {
+    /// バケツのID。
+    pub bucket: BucketId,
+
+    /// 保存されているオブジェクトの数。
+    pub object_count: u64,
+
+    /// 保存されているオブジェクトの内容の合計サイズ（冗長化前、バイト単位）。
+    pub total_bytes: u64,
+
+    /// バケツに設定されている使用量の上限。
+    pub quota: BucketQuota,
+
+    /// セグメント毎の使用量。
+    ///
+    /// セグメント番号の昇順に並んでいる。
+    pub segments: Vec<SegmentUsage>,
+}
+impl BucketUsage {
+    /// セグメント毎の使用量を合算して、新しい`BucketUsage`インスタンスを生成する。
+    pub fn new(bucket: BucketId, quota: BucketQuota, mut segments: Vec<SegmentUsage>) -> Self {
+        segments.sort_by_key(|s| s.segment);
+        BucketUsage {
+            bucket,
+            object_count: segments.iter().map(|s| s.object_count).sum(),
+            total_bytes: segments.iter().map(|s| s.total_bytes).sum(),
+            quota,
+            segments,
+        }
+    }
+
+    /// 使用量が上限を超えているかどうかを判定する。
+    pub fn is_quota_exceeded(&self) -> bool {
+        self.quota.is_exceeded(self.object_count, self.total_bytes)
+    }
+}
+
+/// セグメントの使用量。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentUsage {
+    /// セグメント番号。
+    pub segment: u16,
+
+    /// 保存されているオブジェクトの数。
+    pub object_count: u64,
+
+    /// 保存されているオブジェクトの内容の合計サイズ（冗長化前、バイト単位）。
+    pub total_bytes: u64,
+}
+
 /// メタデータ用のバケツ。
 ///
 /// 他のバケツとは異なり、オブジェクトのデータは全てメモリ上に保持されるため、
 /// PUT/GETは高速だが、メモリ負荷が高い。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MetadataBucket {
     /// バケツのID。
     pub id: BucketId,
@@ -189,10 +549,26 @@ pub struct MetadataBucket {
 
     /// 故障耐性数。
     pub tolerable_faults: u32,
+
+    /// 使用量の上限。
+    #[serde(default)]
+    pub quota: BucketQuota,
+
+    /// 要求で省略された設定の既定値。
+    #[serde(default)]
+    pub defaults: BucketDefaults,
+
+    /// オブジェクトの有効期限に関するルール群。
+    #[serde(default)]
+    pub lifecycle_rules: Vec<LifecycleRule>,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// 複製による冗長化を行うバケツ。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReplicatedBucket {
     /// バケツのID。
     pub id: BucketId,
@@ -214,10 +590,26 @@ pub struct ReplicatedBucket {
     ///
     /// `tolerable_faults + 1`が複製の数となる。
     pub tolerable_faults: u32,
+
+    /// 使用量の上限。
+    #[serde(default)]
+    pub quota: BucketQuota,
+
+    /// 要求で省略された設定の既定値。
+    #[serde(default)]
+    pub defaults: BucketDefaults,
+
+    /// オブジェクトの有効期限に関するルール群。
+    #[serde(default)]
+    pub lifecycle_rules: Vec<LifecycleRule>,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// ErasureCodingによる冗長化を行うバケツ。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DispersedBucket {
     /// バケツのID。
     pub id: BucketId,
@@ -242,4 +634,452 @@ pub struct DispersedBucket {
 
     /// ErasureCodingのデータフラグメント数。
     pub data_fragment_count: u32,
+
+    /// ErasureCodingの方式等の設定。
+    #[serde(default)]
+    pub erasure_coding: ErasureCoding,
+
+    /// 使用量の上限。
+    #[serde(default)]
+    pub quota: BucketQuota,
+
+    /// 要求で省略された設定の既定値。
+    #[serde(default)]
+    pub defaults: BucketDefaults,
+
+    /// オブジェクトの有効期限に関するルール群。
+    #[serde(default)]
+    pub lifecycle_rules: Vec<LifecycleRule>,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
+}
+
+impl DispersedBucket {
+    /// 一つのオブジェクトを符号化した際のフラグメントの総数を返す。
+    ///
+    /// データフラグメント、パリティフラグメント（`tolerable_faults`個）、
+    /// および（`ErasureCodingScheme::LocallyRepairable`の場合には）ローカルパリティフラグメントの合計となる。
+    pub fn fragment_count(&self) -> u32 {
+        self.data_fragment_count
+            + self.tolerable_faults
+            + self
+                .erasure_coding
+                .scheme
+                .local_parity_count(self.data_fragment_count)
+    }
+}
+
+/// オブジェクトのサイズに応じて複製とErasureCodingを使い分けるバケツ。
+///
+/// サイズが`size_threshold`未満のオブジェクトは複製（`ReplicatedBucket`と同様）によって、
+/// それ以上のオブジェクトはErasureCoding（`DispersedBucket`と同様）によって冗長化される。
+/// 両者は同じセグメント群（Raftクラスタ）に保存される。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HybridBucket {
+    /// バケツのID。
+    pub id: BucketId,
+
+    /// バケツのシーケンス番号（登録番号）。
+    ///
+    /// 登録時に自動で採番される。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// バケツが使用するデバイス。
+    pub device: DeviceId,
+
+    /// バケツのセグメント数。
+    #[serde(default)]
+    pub segment_count: u32,
+
+    /// 複製とErasureCodingを切り替えるオブジェクトのサイズ（バイト単位）。
+    pub size_threshold: u64,
+
+    /// 複製されるオブジェクトの故障耐性数。
+    ///
+    /// `replicated_tolerable_faults + 1`が複製の数となる。
+    pub replicated_tolerable_faults: u32,
+
+    /// ErasureCodingされるオブジェクトの故障耐性数。
+    ///
+    /// ErasureCodingのパリティフラグメント数でもある。
+    pub dispersed_tolerable_faults: u32,
+
+    /// ErasureCodingのデータフラグメント数。
+    pub data_fragment_count: u32,
+
+    /// ErasureCodingの方式等の設定。
+    #[serde(default)]
+    pub erasure_coding: ErasureCoding,
+
+    /// 使用量の上限。
+    #[serde(default)]
+    pub quota: BucketQuota,
+
+    /// 要求で省略された設定の既定値。
+    #[serde(default)]
+    pub defaults: BucketDefaults,
+
+    /// オブジェクトの有効期限に関するルール群。
+    #[serde(default)]
+    pub lifecycle_rules: Vec<LifecycleRule>,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
+}
+impl HybridBucket {
+    /// バケツ内の全てのオブジェクトについて保証される故障耐性数を返す。
+    pub fn tolerable_faults(&self) -> u32 {
+        cmp::min(
+            self.replicated_tolerable_faults,
+            self.dispersed_tolerable_faults,
+        )
+    }
+
+    /// Raftクラスタのサイズの決定に用いられる故障耐性数を返す。
+    ///
+    /// 両方の冗長化方式の要求を満たすために、大きい方の値が使用される。
+    pub fn raft_tolerable_faults(&self) -> u32 {
+        cmp::max(
+            self.replicated_tolerable_faults,
+            self.dispersed_tolerable_faults,
+        )
+    }
+
+    /// 一つのオブジェクトをErasureCodingで符号化した際のフラグメントの総数を返す。
+    ///
+    /// `DispersedBucket::fragment_count`と同様に計算される。
+    pub fn fragment_count(&self) -> u32 {
+        self.data_fragment_count
+            + self.dispersed_tolerable_faults
+            + self
+                .erasure_coding
+                .scheme
+                .local_parity_count(self.data_fragment_count)
+    }
+}
+
+/// ErasureCodingの設定。
+///
+/// 各項目が省略された場合には、frugalosの既定値が使用される。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ErasureCoding {
+    /// 符号化方式。
+    #[serde(default)]
+    pub scheme: ErasureCodingScheme,
+
+    /// フラグメントのアライメント（バイト単位）。
+    ///
+    /// 各フラグメントのサイズはこの値の倍数に切り上げられる。
+    /// 2の冪である必要がある。
+    #[serde(default)]
+    pub fragment_alignment: Option<u32>,
+
+    /// フラグメントのチェックサムの計算方式。
+    #[serde(default)]
+    pub checksum: Option<ChecksumAlgorithm>,
+}
+
+/// ErasureCodingの符号化方式。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasureCodingScheme {
+    /// Reed-Solomon符号。
+    #[default]
+    ReedSolomon,
+
+    /// Locally Repairable Codes。
+    ///
+    /// データフラグメント群を`local_group_size`個ずつのグループに分け、
+    /// 各グループにローカルパリティフラグメントを一つずつ追加する。
+    /// 単一フラグメントの修復に必要な読み込み量が、Reed-Solomon符号に比べて少なくて済む。
+    LocallyRepairable {
+        /// ローカルグループ当たりのデータフラグメント数。
+        local_group_size: u32,
+    },
+}
+impl ErasureCodingScheme {
+    /// `data_fragment_count`個のデータフラグメントに対して追加されるローカルパリティフラグメントの数を返す。
+    pub fn local_parity_count(&self, data_fragment_count: u32) -> u32 {
+        match *self {
+            ErasureCodingScheme::ReedSolomon => 0,
+            ErasureCodingScheme::LocallyRepairable {
+                local_group_size: 0,
+            } => 0,
+            ErasureCodingScheme::LocallyRepairable { local_group_size } => {
+                data_fragment_count.div_ceil(local_group_size)
+            }
+        }
+    }
+}
+
+/// フラグメントのチェックサムの計算方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    /// チェックサムを付与しない。
+    Disabled,
+
+    /// CRC32。
+    Crc32,
+
+    /// MD5。
+    Md5,
+}
+
+/// オブジェクトの冗長化方式。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Redundancy {
+    /// 複製による冗長化（`ReplicatedBucket`に対応）。
+    Replicated {
+        /// 故障耐性数。
+        tolerable_faults: u32,
+    },
+
+    /// ErasureCodingによる冗長化（`DispersedBucket`に対応）。
+    Dispersed {
+        /// 故障耐性数。
+        tolerable_faults: u32,
+
+        /// ErasureCodingのデータフラグメント数。
+        data_fragment_count: u32,
+
+        /// ErasureCodingの設定。
+        #[serde(default)]
+        erasure_coding: ErasureCoding,
+    },
+
+    /// オブジェクトのサイズに応じた複製とErasureCodingの使い分け（`HybridBucket`に対応）。
+    ///
+    /// 使い分けの閾値（`HybridBucket::size_threshold`）は変更の対象外である。
+    Hybrid {
+        /// 複製で保存されるオブジェクトの故障耐性数。
+        replicated_tolerable_faults: u32,
+
+        /// ErasureCodingで保存されるオブジェクトの故障耐性数。
+        dispersed_tolerable_faults: u32,
+
+        /// ErasureCodingのデータフラグメント数。
+        data_fragment_count: u32,
+
+        /// ErasureCodingの設定。
+        #[serde(default)]
+        erasure_coding: ErasureCoding,
+    },
+}
+impl Redundancy {
+    /// 故障耐性数を返す。
+    ///
+    /// `Redundancy::Hybrid`の場合には、`HybridBucket::tolerable_faults`と同様に小さい方の値となる。
+    pub fn tolerable_faults(&self) -> u32 {
+        match *self {
+            Redundancy::Replicated { tolerable_faults } => tolerable_faults,
+            Redundancy::Dispersed {
+                tolerable_faults, ..
+            } => tolerable_faults,
+            Redundancy::Hybrid {
+                replicated_tolerable_faults,
+                dispersed_tolerable_faults,
+                ..
+            } => cmp::min(replicated_tolerable_faults, dispersed_tolerable_faults),
+        }
+    }
+
+    /// この冗長化方式を用いるバケツの種類を返す。
+    pub fn kind(&self) -> BucketKind {
+        match *self {
+            Redundancy::Replicated { .. } => BucketKind::Replicated,
+            Redundancy::Dispersed { .. } => BucketKind::Dispersed,
+            Redundancy::Hybrid { .. } => BucketKind::Hybrid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn bucket(value: serde_json::Value) -> Bucket {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn erasure_coding() -> ErasureCoding {
+        ErasureCoding {
+            scheme: ErasureCodingScheme::LocallyRepairable {
+                local_group_size: 2,
+            },
+            fragment_alignment: Some(4096),
+            checksum: Some(ChecksumAlgorithm::Md5),
+        }
+    }
+
+    #[test]
+    fn expiration_works() {
+        let days = |n: u64| Some(Duration::from_secs(n * 24 * 60 * 60));
+        let b = bucket(json!({"replicated": {
+            "id": "b",
+            "device": "d",
+            "tolerable_faults": 1,
+            "lifecycle_rules": [
+                {"prefix": "tmp/a", "expiration_days": 30},
+                {"prefix": "tmp/", "expiration_days": 7},
+                {"prefix": "log/", "expiration_days": 3},
+                {"prefix": "log/", "expiration_days": 5}
+            ]
+        }}));
+
+        // 最も長い接頭辞に該当するルールが優先される（期間の長短には依らない）
+        assert_eq!(b.expiration("tmp/a/x"), days(30));
+        assert_eq!(b.expiration("tmp/b"), days(7));
+
+        // 同じ接頭辞のルール同士では短い方が優先される
+        assert_eq!(b.expiration("log/x"), days(3));
+
+        // どのルールにも該当しない
+        assert_eq!(b.expiration("tmp"), None);
+        assert_eq!(b.expiration("data/x"), None);
+
+        // 接頭辞が空のルールは全てのオブジェクトに該当する
+        let b = bucket(json!({"replicated": {
+            "id": "b",
+            "device": "d",
+            "tolerable_faults": 1,
+            "lifecycle_rules": [
+                {"expiration_days": 365},
+                {"prefix": "tmp/", "expiration_days": 7}
+            ]
+        }}));
+        assert_eq!(b.expiration("tmp/x"), days(7));
+        assert_eq!(b.expiration("data/x"), days(365));
+
+        // ルールが存在しない
+        let b = bucket(json!({"replicated": {"id": "b", "device": "d", "tolerable_faults": 1}}));
+        assert_eq!(b.expiration("tmp/x"), None);
+    }
+
+    #[test]
+    fn dispersed_redundancy_round_trip_works() {
+        let mut b = bucket(json!({"dispersed": {
+            "id": "b",
+            "device": "d",
+            "segment_count": 10,
+            "tolerable_faults": 2,
+            "data_fragment_count": 4,
+            "erasure_coding": {
+                "scheme": {"locally_repairable": {"local_group_size": 2}},
+                "fragment_alignment": 4096,
+                "checksum": "md5"
+            }
+        }}));
+        let redundancy = b.redundancy().unwrap();
+        assert_eq!(
+            redundancy,
+            Redundancy::Dispersed {
+                tolerable_faults: 2,
+                data_fragment_count: 4,
+                erasure_coding: erasure_coding(),
+            }
+        );
+
+        let original = b.clone();
+        b.set_redundancy(redundancy).unwrap();
+        assert_eq!(b, original);
+    }
+
+    #[test]
+    fn set_redundancy_changes_kind() {
+        let mut b = bucket(json!({"replicated": {
+            "id": "b",
+            "seqno": 3,
+            "device": "d",
+            "segment_count": 10,
+            "tolerable_faults": 1,
+            "quota": {"max_objects": 100},
+            "lifecycle_rules": [{"prefix": "tmp/", "expiration_days": 7}],
+            "labels": {"team": "storage"}
+        }}));
+        let original = b.clone();
+
+        let dispersed = Redundancy::Dispersed {
+            tolerable_faults: 2,
+            data_fragment_count: 4,
+            erasure_coding: erasure_coding(),
+        };
+        b.set_redundancy(dispersed.clone()).unwrap();
+        assert_eq!(b.kind(), BucketKind::Dispersed);
+        assert_eq!(b.redundancy(), Some(dispersed));
+        assert_eq!(b.id(), original.id());
+        assert_eq!(b.seqno(), 3);
+        assert_eq!(b.device(), original.device());
+        assert_eq!(b.segment_count(), 10);
+        assert_eq!(b.quota(), original.quota());
+        assert_eq!(b.lifecycle_rules(), original.lifecycle_rules());
+        assert_eq!(b.labels(), original.labels());
+
+        b.set_redundancy(original.redundancy().unwrap()).unwrap();
+        assert_eq!(b, original);
+    }
+
+    #[test]
+    fn set_redundancy_rejects_metadata_bucket() {
+        let mut b = bucket(json!({"metadata": {
+            "id": "b",
+            "device": "d",
+            "tolerable_faults": 1
+        }}));
+        assert_eq!(b.redundancy(), None);
+        let redundancy = Redundancy::Replicated {
+            tolerable_faults: 1,
+        };
+        assert!(b.set_redundancy(redundancy).is_err());
+    }
+
+    #[test]
+    fn hybrid_redundancy_works() {
+        let mut b = bucket(json!({"hybrid": {
+            "id": "b",
+            "device": "d",
+            "size_threshold": 1024,
+            "replicated_tolerable_faults": 2,
+            "dispersed_tolerable_faults": 1,
+            "data_fragment_count": 4
+        }}));
+        let original = b.clone();
+        let redundancy = b.redundancy().unwrap();
+        assert_eq!(redundancy.kind(), BucketKind::Hybrid);
+        assert_eq!(redundancy.tolerable_faults(), 1);
+        b.set_redundancy(redundancy).unwrap();
+        assert_eq!(b, original);
+
+        let hybrid = Redundancy::Hybrid {
+            replicated_tolerable_faults: 1,
+            dispersed_tolerable_faults: 2,
+            data_fragment_count: 6,
+            erasure_coding: erasure_coding(),
+        };
+        b.set_redundancy(hybrid.clone()).unwrap();
+        assert_eq!(b.redundancy(), Some(hybrid.clone()));
+        if let Bucket::Hybrid(ref h) = b {
+            assert_eq!(h.size_threshold, 1024);
+        } else {
+            panic!("{:?}", b);
+        }
+
+        // HybridBucketから他の種類への変更は可能
+        let replicated = Redundancy::Replicated {
+            tolerable_faults: 1,
+        };
+        b.set_redundancy(replicated.clone()).unwrap();
+        assert_eq!(b.redundancy(), Some(replicated));
+
+        // 他の種類からHybridBucketへの変更は`size_threshold`が定まらないため不可
+        assert!(b.set_redundancy(hybrid).is_err());
+        assert_eq!(b.kind(), BucketKind::Replicated);
+    }
 }
@@ -0,0 +1,175 @@
+//! 構成情報関連のエンティティ定義。
+use std::fmt;
+
+use crate::entity::bucket::{Bucket, BucketId};
+use crate::entity::device::{Device, DeviceId};
+use crate::entity::device_graph::DeviceGraph;
+use crate::entity::server::{Server, ServerId};
+use crate::time::Seconds;
+
+// FIXME: 構造体にする
+/// 構成変更を行った主体（利用者やツール）の識別子。
+pub type ActorId = String;
+
+/// 構成情報のリビジョン。
+///
+/// サーバ・デバイス・バケツのいずれかが登録ないし削除される度に単調増加する。
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct ConfigRevision(pub u64);
+
+/// クラスタ全体の構成情報。
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    /// サーバ群。
+    #[serde(default)]
+    pub servers: Vec<Server>,
+
+    /// デバイス群。
+    #[serde(default)]
+    pub devices: Vec<Device>,
+
+    /// バケツ群。
+    #[serde(default)]
+    pub buckets: Vec<Bucket>,
+}
+impl ClusterConfig {
+    /// 指定デバイスを使用しているバケツ群を返す。
+    ///
+    /// バケツのデバイスが指定デバイス自身の場合に加えて、
+    /// 指定デバイスを配下に含む仮想デバイスの場合も対象となる。
+    pub fn buckets_using_device(&self, device: &DeviceId) -> Vec<&Bucket> {
+        let graph = DeviceGraph::new(&self.devices);
+        let mut devices = graph.ancestors(device);
+        devices.insert(device);
+        self.buckets
+            .iter()
+            .filter(|b| devices.contains(b.device()))
+            .collect()
+    }
+
+    /// 指定サーバ上の物理デバイス群を返す。
+    pub fn devices_on_server(&self, server: &ServerId) -> Vec<&Device> {
+        self.devices
+            .iter()
+            .filter(|d| d.server() == Some(server))
+            .collect()
+    }
+}
+
+/// 構成情報を構成するエンティティ。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigEntity {
+    /// サーバ。
+    Server(Server),
+
+    /// デバイス。
+    Device(Device),
+
+    /// バケツ。
+    Bucket(Bucket),
+}
+impl ConfigEntity {
+    /// エンティティの識別子を返す。
+    pub fn to_id(&self) -> ConfigEntityId {
+        match *self {
+            ConfigEntity::Server(ref s) => ConfigEntityId::Server(s.id.clone()),
+            ConfigEntity::Device(ref d) => ConfigEntityId::Device(d.id().clone()),
+            ConfigEntity::Bucket(ref b) => ConfigEntityId::Bucket(b.id().clone()),
+        }
+    }
+}
+
+/// 構成情報を構成するエンティティの識別子。
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigEntityId {
+    /// サーバのID。
+    Server(ServerId),
+
+    /// デバイスのID。
+    Device(DeviceId),
+
+    /// バケツのID。
+    Bucket(BucketId),
+}
+impl fmt::Display for ConfigEntityId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigEntityId::Server(ref id) => write!(f, "server {}", id),
+            ConfigEntityId::Device(ref id) => write!(f, "device {}", id),
+            ConfigEntityId::Bucket(ref id) => write!(f, "bucket {}", id),
+        }
+    }
+}
+
+/// 構成情報に対する一つの変更。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChange {
+    /// サーバが登録（更新）された。
+    PutServer(Server),
+
+    /// サーバが削除された。
+    DeleteServer(ServerId),
+
+    /// デバイスが登録（更新）された。
+    PutDevice(Device),
+
+    /// デバイスが削除された。
+    DeleteDevice(DeviceId),
+
+    /// バケツが登録（更新）された。
+    PutBucket(Bucket),
+
+    /// バケツが削除された。
+    DeleteBucket(BucketId),
+}
+
+/// あるリビジョン以降に行われた構成変更群。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChanges {
+    /// `changes`を全て適用した時点でのリビジョン。
+    pub revision: ConfigRevision,
+
+    /// 変更群。
+    ///
+    /// 古いものから順に並んでいる。
+    /// 待機中にタイムアウトした場合には空となる。
+    pub changes: Vec<ConfigChange>,
+}
+
+/// 構成変更の監査記録。
+///
+/// `schema::config`経由で行われた全ての登録・削除について一つずつ記録される。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 変更後のリビジョン。
+    pub revision: ConfigRevision,
+
+    /// 変更が行われた時刻（UNIXエポックからの経過秒数）。
+    pub timestamp: Seconds,
+
+    /// 変更を行った主体。
+    ///
+    /// 要求に主体が指定されていなかった場合には`None`となる。
+    #[serde(default)]
+    pub actor: Option<ActorId>,
+
+    /// 変更対象のエンティティ。
+    pub target: ConfigEntityId,
+
+    /// 変更前の値。
+    ///
+    /// 新規登録の場合には`None`となる。
+    #[serde(default)]
+    pub previous: Option<ConfigEntity>,
+
+    /// 変更後の値。
+    ///
+    /// 削除の場合には`None`となる。
+    #[serde(default)]
+    pub current: Option<ConfigEntity>,
+}
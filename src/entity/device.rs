@@ -1,7 +1,13 @@
 //! デバイス関連のエンティティ定義。
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeSet;
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use trackable::error::ErrorKindExt;
 
+use crate::entity::failure_domain::{FailureDomain, FailureDomainLevel};
+use crate::entity::label::Labels;
 use crate::entity::server::ServerId;
 use crate::{Error, ErrorKind, Result};
 
@@ -27,10 +33,18 @@ pub struct DeviceSummary {
     /// デバイスの種類。
     #[serde(rename = "type")]
     pub kind: DeviceKind,
+
+    /// デバイスの稼働状態。
+    #[serde(default)]
+    pub state: DeviceState,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// デバイスの種類。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DeviceKind {
     /// 仮想デバイス。
@@ -44,7 +58,7 @@ pub enum DeviceKind {
 }
 
 /// デバイス。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Device {
     /// 仮想デバイス。
@@ -64,7 +78,10 @@ impl Default for Device {
             capacity: 0,
             seqno: 0,
             weight: Default::default(),
+            state: DeviceState::default(),
             server: String::new(),
+            failure_domain: FailureDomain::default(),
+            labels: Labels::new(),
         })
     }
 }
@@ -75,6 +92,8 @@ impl Device {
             id: self.id().to_owned(),
             server: self.server().cloned(),
             kind: self.kind(),
+            state: self.state(),
+            labels: self.labels().clone(),
         }
     }
 
@@ -101,6 +120,26 @@ impl Device {
         }
     }
 
+    /// デバイス自身に指定された障害ドメインを返す。
+    ///
+    /// 仮想デバイスの場合には`None`となる。
+    pub fn failure_domain(&self) -> Option<&FailureDomain> {
+        match *self {
+            Device::Memory(ref d) => Some(&d.failure_domain),
+            Device::File(ref d) => Some(&d.failure_domain),
+            _ => None,
+        }
+    }
+
+    /// ラベル群を返す。
+    pub fn labels(&self) -> &Labels {
+        match *self {
+            Device::Virtual(ref d) => &d.labels,
+            Device::Memory(ref d) => &d.labels,
+            Device::File(ref d) => &d.labels,
+        }
+    }
+
     /// IDを返す。
     pub fn id(&self) -> &DeviceId {
         match *self {
@@ -127,12 +166,140 @@ impl Device {
             Device::File(ref d) => d.seqno,
         }
     }
+
+    /// 稼働状態を設定する。
+    pub fn set_state(&mut self, state: DeviceState) {
+        match *self {
+            Device::Virtual(ref mut d) => d.state = state,
+            Device::Memory(ref mut d) => d.state = state,
+            Device::File(ref mut d) => d.state = state,
+        }
+    }
+
+    /// 稼働状態を返す。
+    pub fn state(&self) -> DeviceState {
+        match *self {
+            Device::Virtual(ref d) => d.state,
+            Device::Memory(ref d) => d.state,
+            Device::File(ref d) => d.state,
+        }
+    }
+}
+
+/// デバイスの稼働状態。
+///
+/// 仮想デバイスの状態は、その配下の全てのデバイスに対して適用される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceState {
+    /// 通常の稼働状態。
+    #[default]
+    Active,
+
+    /// 退役準備中。
+    ///
+    /// 新規のセグメントは割り当てられず、保持しているデータは他のデバイスに移行される。
+    Draining,
+
+    /// 保守中。
+    ///
+    /// 新規のセグメントは割り当てられないが、保持しているデータの移行も行われない。
+    Maintenance,
+
+    /// 退役済み。
+    ///
+    /// データの移行が完了しており、もはやデータを保持していない。
+    Retired,
+}
+impl DeviceState {
+    /// 新規のセグメントを割り当て可能な状態かどうかを判定する。
+    pub fn is_allocatable(self) -> bool {
+        self == DeviceState::Active
+    }
+
+    /// `self`から`next`への遷移が可能かどうかを判定する。
+    ///
+    /// 同じ状態への遷移は常に可能である。
+    /// `Retired`へは`Draining`からのみ遷移可能で、`Retired`からは他の状態に遷移できない。
+    ///
+    /// なお`Draining`から`Retired`への遷移は、データの移行が完了していない場合にはserver側で拒否される。
+    pub fn can_transition_to(self, next: DeviceState) -> bool {
+        use self::DeviceState::*;
+        match (self, next) {
+            (a, b) if a == b => true,
+            (Active, Draining) | (Active, Maintenance) => true,
+            (Maintenance, Active) | (Maintenance, Draining) => true,
+            (Draining, Active) | (Draining, Retired) => true,
+            _ => false,
+        }
+    }
+}
+
+/// デバイスの使用状況。
+///
+/// 仮想デバイスの場合には、配下の物理デバイス群の使用状況を合算した値となる。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceUsage {
+    /// デバイスのID。
+    pub device: DeviceId,
+
+    /// 容量（バイト単位）。
+    pub capacity: u64,
+
+    /// 使用済みのバイト数。
+    pub used_bytes: u64,
+
+    /// 空きバイト数。
+    pub free_bytes: u64,
+
+    /// 保存されているオブジェクト（複製）の数。
+    pub object_count: u64,
+
+    /// 保存されているフラグメント（ErasureCodingの断片）の数。
+    pub fragment_count: u64,
+}
+impl DeviceUsage {
+    /// 使用率をパーセント単位で返す。
+    ///
+    /// 容量が`0`の場合には`0.0`を返す。
+    pub fn percent_full(&self) -> f64 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.used_bytes as f64 * 100.0 / self.capacity as f64
+        }
+    }
+
+    /// 子デバイス群の使用状況を合算して、仮想デバイス`device`の使用状況を求める。
+    pub fn aggregate<'a, I>(device: DeviceId, children: I) -> Self
+    where
+        I: IntoIterator<Item = &'a DeviceUsage>,
+    {
+        children.into_iter().fold(
+            DeviceUsage {
+                device,
+                capacity: 0,
+                used_bytes: 0,
+                free_bytes: 0,
+                object_count: 0,
+                fragment_count: 0,
+            },
+            |mut acc, u| {
+                acc.capacity += u.capacity;
+                acc.used_bytes += u.used_bytes;
+                acc.free_bytes += u.free_bytes;
+                acc.object_count += u.object_count;
+                acc.fragment_count += u.fragment_count;
+                acc
+            },
+        )
+    }
 }
 
 /// 仮想デバイス。
 ///
 /// 他のデバイス群をまとめるため構成要素。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VirtualDevice {
     /// ID。
     pub id: DeviceId,
@@ -147,18 +314,28 @@ pub struct VirtualDevice {
     #[serde(default)]
     pub weight: Weight,
 
+    /// 稼働状態。
+    ///
+    /// `SetDeviceStateRpc`によってのみ変更され、`PutDeviceRpc`による更新時には無視される。
+    #[serde(default)]
+    pub state: DeviceState,
+
     /// 子デバイス群。
     pub children: BTreeSet<DeviceId>,
     #[serde(default)]
 
     /// オブジェクトの割当方針。
     pub policy: SegmentAllocationPolicy,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// メモリデバイス。
 ///
 /// ここに保存されたオブジェクト群は永続化されない。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MemoryDevice {
     /// ID。
     pub id: DeviceId,
@@ -173,11 +350,28 @@ pub struct MemoryDevice {
     #[serde(default)]
     pub weight: Weight,
 
+    /// 稼働状態。
+    ///
+    /// `SetDeviceStateRpc`によってのみ変更され、`PutDeviceRpc`による更新時には無視される。
+    #[serde(default)]
+    pub state: DeviceState,
+
     /// デバイスを保持しているサーバ。
     pub server: ServerId,
 
     /// 容量（バイト単位）。
     pub capacity: u64,
+
+    /// デバイスが属する障害ドメイン。
+    ///
+    /// 指定されていない階層については、サーバの`failure_domain`の値が使用される
+    /// （`FailureDomain::resolve`参照）。
+    #[serde(default)]
+    pub failure_domain: FailureDomain,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 impl MemoryDevice {
     /// 重みを返す。
@@ -187,7 +381,7 @@ impl MemoryDevice {
 }
 
 /// ファイルデバイス。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileDevice {
     /// ID。
     pub id: DeviceId,
@@ -202,75 +396,396 @@ pub struct FileDevice {
     #[serde(default)]
     pub weight: Weight,
 
+    /// 稼働状態。
+    ///
+    /// `SetDeviceStateRpc`によってのみ変更され、`PutDeviceRpc`による更新時には無視される。
+    #[serde(default)]
+    pub state: DeviceState,
+
     /// デバイスを保持しているサーバ。
     pub server: ServerId,
 
-    /// 容量（バイト単位）。
+    /// 容量。
     ///
-    /// `0`が指定された場合には、ディスクの空き容量の99%の値が使用される。
+    /// 省略された場合には、ファイルシステムの空き容量の99%が使用される。
     #[serde(default)]
-    pub capacity: u64,
+    pub capacity: CapacitySpec,
 
     /// ファイルパス。
     pub filepath: PathBuf,
+
+    /// デバイスが属する障害ドメイン。
+    ///
+    /// 指定されていない階層については、サーバの`failure_domain`の値が使用される
+    /// （`FailureDomain::resolve`参照）。
+    #[serde(default)]
+    pub failure_domain: FailureDomain,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 impl FileDevice {
     /// 重みを返す。
+    ///
+    /// 容量が割合で指定されている場合には、ファイルシステムの情報は参照せずに容量を`0`として計算する。
+    /// 実際の容量に基づく重みが必要な場合には`resolved_weight`を使用する。
     pub fn weight(&self) -> u64 {
-        self.weight.calculate(self.capacity)
+        let capacity = match self.capacity {
+            CapacitySpec::Bytes(n) => n,
+            _ => 0,
+        };
+        self.weight.calculate(capacity)
+    }
+
+    /// `capacity()`で解決した容量に基づく重みを返す。
+    pub fn resolved_weight(&self) -> Result<u64> {
+        let capacity = track!(self.capacity())?;
+        Ok(self.weight.calculate(capacity))
     }
 
-    /// キャパシティを返す。
+    /// キャパシティ（バイト単位）を返す。
+    ///
+    /// 容量が割合で指定されている場合には、
+    /// `filepath`が置かれるファイルシステムの情報を参照して計算する。
+    /// この際にディレクトリの作成等の副作用は発生しない。
     pub fn capacity(&self) -> Result<u64> {
-        if self.capacity == 0 {
-            // for this case,
-            // assign 99% of available space of the device
+        let dir = track_assert_some!(
+            self.filepath.parent(),
+            ErrorKind::InvalidInput,
+            "File device path has no parent directory: device={:?}, path={:?}",
+            self.id,
+            self.filepath
+        );
+        track!(self.capacity.resolve(dir), "device={:?}", self.id)
+    }
+}
 
-            // FIXME: `0`ではなくて明示的な省略を用意する
-            // FIXME: エラーハンドリング（そもそもリクエスト受けた段階でバリデーションを行うべき）
-            let dir = track_assert_some!(self.filepath.parent(), ErrorKind::InvalidInput);
-            track!(std::fs::create_dir_all(dir).map_err(Error::from))?;
+/// ファイルデバイスの容量指定。
+///
+/// JSON等の人が読み書きする形式では、以下のような文字列で表現される:
+///
+/// - `"1048576"`, `"500GiB"`, `"1.5TB"`: バイト単位の絶対値
+///   - 単位には`B`、`KB`/`MB`/`GB`/`TB`/`PB`（1000の冪）、
+///     `KiB`/`MiB`/`GiB`/`TiB`/`PiB`（1024の冪）が使用可能（大文字小文字は区別しない）
+/// - `"80%"`: ファイルシステム全体の容量に対する割合
+/// - `"99% free"`: ファイルシステムの空き容量に対する割合
+///
+/// 後方互換性のために整数値も受け付ける。
+/// `0`は（整数値・文字列のいずれでも）従来通り`CapacitySpec::default()`として、それ以外はバイト数として扱われる。
+/// バイト数の上限は`MAX_CAPACITY_BYTES`。
+///
+/// バイナリ形式（e.g., bincode）では通常の列挙型としてエンコードされる。
+/// 従来の`capacity: u64`形式は`legacy::FileDevice`が扱う。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapacitySpec {
+    /// バイト単位の絶対値。
+    Bytes(u64),
+
+    /// ファイルシステム全体の容量に対する割合（パーセント単位）。
+    PercentOfFilesystem(f64),
+
+    /// ファイルシステムの空き容量に対する割合（パーセント単位）。
+    PercentOfFree(f64),
+}
+impl CapacitySpec {
+    /// `dir`が置かれるファイルシステムに対して、容量指定をバイト数に解決する。
+    ///
+    /// `dir`自体が存在しない場合には、最も近い既存の祖先ディレクトリのファイルシステムが参照される。
+    /// `CapacitySpec::Bytes`の場合にはファイルシステムは参照されない。
+    pub fn resolve<P: AsRef<Path>>(&self, dir: P) -> Result<u64> {
+        let percent = match *self {
+            CapacitySpec::Bytes(n) => return Ok(n),
+            CapacitySpec::PercentOfFilesystem(p) | CapacitySpec::PercentOfFree(p) => p,
+        };
+        track_assert!(
+            percent > 0.0 && percent <= 100.0,
+            ErrorKind::InvalidInput,
+            "Capacity percentage out of range (0, 100]: {}",
+            percent
+        );
+
+        let dir = dir.as_ref();
+        let existing = track_assert_some!(
+            dir.ancestors().find(|d| d.exists()),
+            ErrorKind::InvalidInput,
+            "No existing ancestor directory: {:?}",
+            dir
+        );
+        track_assert!(
+            existing.is_dir(),
+            ErrorKind::InvalidInput,
+            "Not a directory: {:?} (while resolving {:?})",
+            existing,
+            dir
+        );
+
+        let stats = track!(filesystem_stats(existing), "dir={:?}", existing)?;
+        let base = match *self {
+            CapacitySpec::PercentOfFilesystem(_) => stats.total,
+            _ => stats.available,
+        };
+        Ok((base as f64 * percent / 100.0) as u64)
+    }
+}
+impl Default for CapacitySpec {
+    fn default() -> Self {
+        CapacitySpec::PercentOfFree(99.0)
+    }
+}
+impl fmt::Display for CapacitySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CapacitySpec::Bytes(n) => {
+                let unit = DECIMAL_UNITS
+                    .iter()
+                    .chain(BINARY_UNITS.iter())
+                    .filter(|u| n != 0 && n % u.1 == 0)
+                    .max_by_key(|u| u.1);
+                if let Some(&(unit, size)) = unit {
+                    write!(f, "{}{}", n / size, unit)
+                } else {
+                    write!(f, "{}", n)
+                }
+            }
+            CapacitySpec::PercentOfFilesystem(p) => write!(f, "{}%", p),
+            CapacitySpec::PercentOfFree(p) => write!(f, "{}% free", p),
+        }
+    }
+}
+impl FromStr for CapacitySpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        if let Some(i) = lower.find('%') {
+            let percent: f64 = track!(lower[..i]
+                .trim()
+                .parse()
+                .map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))))?;
+            track_assert!(
+                percent > 0.0 && percent <= 100.0,
+                ErrorKind::InvalidInput,
+                "Capacity percentage out of range (0, 100]: {:?}",
+                s
+            );
+            return match lower[i + 1..].trim() {
+                "" => Ok(CapacitySpec::PercentOfFilesystem(percent)),
+                "free" => Ok(CapacitySpec::PercentOfFree(percent)),
+                _ => track_panic!(ErrorKind::InvalidInput, "Malformed capacity: {:?}", s),
+            };
+        }
 
-            let path = track_try_unwrap!(
-                std::ffi::CString::new(dir.to_string_lossy().to_string()).map_err(Error::from)
+        let split = lower
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(lower.len());
+        let (number, unit) = (&lower[..split], lower[split..].trim());
+        let unit_size = match unit {
+            "" | "b" => 1,
+            _ => track_assert_some!(
+                DECIMAL_UNITS
+                    .iter()
+                    .chain(BINARY_UNITS.iter())
+                    .find(|u| u.0.eq_ignore_ascii_case(unit))
+                    .map(|u| u.1),
+                ErrorKind::InvalidInput,
+                "Unknown capacity unit: {:?}",
+                s
+            ),
+        };
+        let bytes = if number.contains('.') {
+            let n: f64 = track!(number
+                .parse()
+                .map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))))?;
+            let bytes = n * unit_size as f64;
+            track_assert!(
+                bytes.is_finite() && bytes <= MAX_CAPACITY_BYTES as f64,
+                ErrorKind::InvalidInput,
+                "Capacity too large: {:?}",
+                s
+            );
+            bytes as u64
+        } else {
+            let n: u64 = track!(number.parse().map_err(Error::from), "capacity={:?}", s)?;
+            let bytes = track_assert_some!(
+                n.checked_mul(unit_size),
+                ErrorKind::InvalidInput,
+                "Capacity too large: {:?}",
+                s
             );
+            track_assert!(
+                bytes <= MAX_CAPACITY_BYTES,
+                ErrorKind::InvalidInput,
+                "Capacity too large: {:?}",
+                s
+            );
+            bytes
+        };
+        if bytes == 0 {
+            // 整数値の`0`と同様に、従来の既定値として扱う
+            return Ok(CapacitySpec::default());
+        }
+        Ok(CapacitySpec::Bytes(bytes))
+    }
+}
+impl Serialize for CapacitySpec {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            CapacitySpecRepr::from(*self).serialize(serializer)
+        }
+    }
+}
+impl<'de> Deserialize<'de> for CapacitySpec {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(CapacitySpecVisitor)
+        } else {
+            CapacitySpecRepr::deserialize(deserializer).map(CapacitySpec::from)
+        }
+    }
+}
+
+const DECIMAL_UNITS: [(&str, u64); 5] = [
+    ("KB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("PB", 1_000_000_000_000_000),
+];
+const BINARY_UNITS: [(&str, u64); 5] = [
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+    ("PiB", 1 << 50),
+];
+
+/// `CapacitySpec::Bytes`で指定可能な最大のバイト数。
+pub const MAX_CAPACITY_BYTES: u64 = i64::MAX as u64;
+
+/// バイナリ形式（e.g., bincode）での`CapacitySpec`の表現。
+///
+/// バリアントの順番を変えてはいけない。
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "CapacitySpec")]
+enum CapacitySpecRepr {
+    Bytes(u64),
+    PercentOfFilesystem(f64),
+    PercentOfFree(f64),
+}
+impl From<CapacitySpec> for CapacitySpecRepr {
+    fn from(f: CapacitySpec) -> Self {
+        match f {
+            CapacitySpec::Bytes(n) => CapacitySpecRepr::Bytes(n),
+            CapacitySpec::PercentOfFilesystem(p) => CapacitySpecRepr::PercentOfFilesystem(p),
+            CapacitySpec::PercentOfFree(p) => CapacitySpecRepr::PercentOfFree(p),
+        }
+    }
+}
+impl From<CapacitySpecRepr> for CapacitySpec {
+    fn from(f: CapacitySpecRepr) -> Self {
+        match f {
+            CapacitySpecRepr::Bytes(n) => CapacitySpec::Bytes(n),
+            CapacitySpecRepr::PercentOfFilesystem(p) => CapacitySpec::PercentOfFilesystem(p),
+            CapacitySpecRepr::PercentOfFree(p) => CapacitySpec::PercentOfFree(p),
+        }
+    }
+}
+
+struct CapacitySpecVisitor;
+impl<'de> de::Visitor<'de> for CapacitySpecVisitor {
+    type Value = CapacitySpec;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a capacity such as \"500GiB\", \"80%\" or \"99% free\"")
+    }
 
-            let available_space: u64 = track!(calc_available_space(&path))?;
-            Ok((available_space / 100) * 99)
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v == 0 {
+            Ok(CapacitySpec::default())
         } else {
-            Ok(self.capacity)
+            Ok(CapacitySpec::Bytes(v))
         }
     }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if v < 0 {
+            Err(E::invalid_value(de::Unexpected::Signed(v), &self))
+        } else {
+            self.visit_u64(v as u64)
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+struct FilesystemStats {
+    total: u64,
+    available: u64,
+}
+
+fn filesystem_stats(dir: &Path) -> Result<FilesystemStats> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = track!(std::ffi::CString::new(dir.as_os_str().as_bytes()).map_err(Error::from))?;
+    track!(statvfs(&path))
 }
 
 #[cfg(target_os = "macos")]
-fn calc_available_space(path: &std::ffi::CString) -> Result<u64> {
+fn statvfs(path: &std::ffi::CString) -> Result<FilesystemStats> {
     // on OS X,
     // statvfs's f_bsize != statfs's s_bsize
     let mut s: libc::statfs = unsafe { std::mem::zeroed() };
     let result = unsafe { libc::statfs(path.as_ptr(), (&mut s) as _) };
     if result == 0 {
-        Ok(u64::from(s.f_bsize) * s.f_bavail)
+        Ok(FilesystemStats {
+            total: u64::from(s.f_bsize) * s.f_blocks,
+            available: u64::from(s.f_bsize) * s.f_bavail,
+        })
     } else {
         track!(Err(Error::from(std::io::Error::last_os_error())))
     }
 }
 #[cfg(not(target_os = "macos"))]
-fn calc_available_space(path: &std::ffi::CString) -> Result<u64> {
+fn statvfs(path: &std::ffi::CString) -> Result<FilesystemStats> {
     let mut s: libc::statvfs = unsafe { std::mem::zeroed() };
     let result = unsafe { libc::statvfs(path.as_ptr(), (&mut s) as _) };
     if result == 0 {
-        Ok(s.f_bsize * s.f_bavail)
+        Ok(FilesystemStats {
+            total: s.f_frsize * s.f_blocks,
+            available: s.f_frsize * s.f_bavail,
+        })
     } else {
         track!(Err(Error::from(std::io::Error::last_os_error())))
     }
 }
 
 /// デバイスの重み。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Weight {
     /// 自動計算。
+    #[default]
     Auto,
 
     /// 絶対値の重み。
@@ -289,14 +804,9 @@ impl Weight {
         }
     }
 }
-impl Default for Weight {
-    fn default() -> Self {
-        Weight::Auto
-    }
-}
 
 /// セグメントの割当方針。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum SegmentAllocationPolicy {
     /// 可能であれば、同じセグメント内のノード群には別々のデバイスを割り当てる。
     #[serde(rename = "SCATTER_IF_POSSIBLE")]
@@ -317,10 +827,181 @@ pub enum SegmentAllocationPolicy {
     /// 同じセグメント内のノード群について、各デバイスに含まれるノードの個数がなるべく均等になるように割り当てる。
     /// ノードの個数 <= デバイスの個数であれば Scatter と同じ。
     #[serde(rename = "AS_EVEN_AS_POSSIBLE")]
+    #[default]
     AsEvenAsPossible = 4,
+
+    /// 同じセグメント内のノード群には、別々のゾーンに属する物理デバイスを割り当てる。
+    ///
+    /// 配下の物理デバイス群が属するゾーンの数がバケツの`device_group_size()`未満の場合には、
+    /// そのバケツの登録はエラーとなる。
+    #[serde(rename = "SCATTER_BY_ZONE")]
+    ScatterByZone = 5,
+
+    /// 同じセグメント内のノード群には、別々のラックに属する物理デバイスを割り当てる。
+    ///
+    /// 配下の物理デバイス群が属するラックの数がバケツの`device_group_size()`未満の場合には、
+    /// そのバケツの登録はエラーとなる。
+    #[serde(rename = "SCATTER_BY_RACK")]
+    ScatterByRack = 6,
+
+    /// 同じセグメント内のノード群には、別々のホストに属する物理デバイスを割り当てる。
+    ///
+    /// 配下の物理デバイス群が属するホストの数がバケツの`device_group_size()`未満の場合には、
+    /// そのバケツの登録はエラーとなる。
+    #[serde(rename = "SCATTER_BY_HOST")]
+    ScatterByHost = 7,
 }
-impl Default for SegmentAllocationPolicy {
-    fn default() -> Self {
-        SegmentAllocationPolicy::AsEvenAsPossible
+impl SegmentAllocationPolicy {
+    /// 割当の際に分散の単位とする障害ドメインの階層を返す。
+    ///
+    /// 障害ドメインを考慮しない方針の場合には`None`となる。
+    pub fn failure_domain_level(&self) -> Option<FailureDomainLevel> {
+        match *self {
+            SegmentAllocationPolicy::ScatterByZone => Some(FailureDomainLevel::Zone),
+            SegmentAllocationPolicy::ScatterByRack => Some(FailureDomainLevel::Rack),
+            SegmentAllocationPolicy::ScatterByHost => Some(FailureDomainLevel::Host),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytecodec::bincode_codec::{BincodeDecoder, BincodeEncoder};
+    use bytecodec::{DecodeExt, EncodeExt};
+
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    fn parse(s: &str) -> Result<CapacitySpec> {
+        s.parse()
+    }
+
+    #[test]
+    fn capacity_spec_parses_units() {
+        assert_eq!(parse("1048576").unwrap(), CapacitySpec::Bytes(1_048_576));
+        assert_eq!(parse("512B").unwrap(), CapacitySpec::Bytes(512));
+        assert_eq!(parse("500GiB").unwrap(), CapacitySpec::Bytes(500 * GIB));
+        assert_eq!(parse("500 gib").unwrap(), CapacitySpec::Bytes(500 * GIB));
+        assert_eq!(
+            parse("2TB").unwrap(),
+            CapacitySpec::Bytes(2_000_000_000_000)
+        );
+        assert_eq!(
+            parse("1.5TB").unwrap(),
+            CapacitySpec::Bytes(1_500_000_000_000)
+        );
+        assert_eq!(parse("0.5KiB").unwrap(), CapacitySpec::Bytes(512));
+        assert!(parse("10XB").is_err());
+        assert!(parse("GiB").is_err());
+        assert!(parse("-1").is_err());
+    }
+
+    #[test]
+    fn capacity_spec_zero_means_default() {
+        assert_eq!(parse("0").unwrap(), CapacitySpec::default());
+        assert_eq!(parse("0GiB").unwrap(), CapacitySpec::default());
+
+        let device: FileDevice =
+            serde_json::from_str(r#"{"id":"d","server":"s","filepath":"/tmp/d","capacity":0}"#)
+                .unwrap();
+        assert_eq!(device.capacity, CapacitySpec::default());
+    }
+
+    #[test]
+    fn capacity_spec_percent_bounds() {
+        assert_eq!(
+            parse("80%").unwrap(),
+            CapacitySpec::PercentOfFilesystem(80.0)
+        );
+        assert_eq!(
+            parse("100%").unwrap(),
+            CapacitySpec::PercentOfFilesystem(100.0)
+        );
+        assert_eq!(
+            parse("99.5% FREE").unwrap(),
+            CapacitySpec::PercentOfFree(99.5)
+        );
+        assert!(parse("0%").is_err());
+        assert!(parse("100.1%").is_err());
+        assert!(parse("-5%").is_err());
+        assert!(parse("50% used").is_err());
+    }
+
+    #[test]
+    fn capacity_spec_rejects_overflow() {
+        assert!(parse("18446744073709551615").is_err());
+        assert!(parse("20000PB").is_err());
+        assert!(parse("1e30").is_err());
+        assert!(parse("99999999999999999999.5PiB").is_err());
+        assert_eq!(
+            parse(&MAX_CAPACITY_BYTES.to_string()).unwrap(),
+            CapacitySpec::Bytes(MAX_CAPACITY_BYTES)
+        );
+    }
+
+    #[test]
+    fn capacity_spec_display_works() {
+        assert_eq!(CapacitySpec::Bytes(500 * GIB).to_string(), "500GiB");
+        assert_eq!(CapacitySpec::Bytes(1_500_000_000_000).to_string(), "1500GB");
+        assert_eq!(CapacitySpec::Bytes(1023).to_string(), "1023");
+        assert_eq!(CapacitySpec::PercentOfFilesystem(80.0).to_string(), "80%");
+        assert_eq!(CapacitySpec::default().to_string(), "99% free");
+
+        for spec in &[
+            CapacitySpec::Bytes(500 * GIB),
+            CapacitySpec::Bytes(1023),
+            CapacitySpec::PercentOfFilesystem(12.5),
+            CapacitySpec::PercentOfFree(99.0),
+        ] {
+            assert_eq!(parse(&spec.to_string()).unwrap(), *spec);
+        }
+    }
+
+    #[test]
+    fn capacity_spec_binary_round_trips() {
+        for spec in &[
+            CapacitySpec::Bytes(500 * GIB),
+            CapacitySpec::Bytes(u64::MAX),
+            CapacitySpec::PercentOfFilesystem(80.0),
+            CapacitySpec::PercentOfFree(99.0),
+            CapacitySpec::PercentOfFree(12.345_678_9),
+        ] {
+            let bytes = BincodeEncoder::<CapacitySpec>::new()
+                .encode_into_bytes(*spec)
+                .unwrap();
+            let decoded = BincodeDecoder::<CapacitySpec>::new()
+                .decode_from_bytes(&bytes)
+                .unwrap();
+            assert_eq!(decoded, *spec);
+        }
+
+        // バリアントの番号に続いて値がそのままエンコードされる
+        let bytes = BincodeEncoder::<CapacitySpec>::new()
+            .encode_into_bytes(CapacitySpec::PercentOfFree(50.0))
+            .unwrap();
+        assert_eq!(&bytes[..4], [2, 0, 0, 0]);
+        assert_eq!(&bytes[4..], 50.0f64.to_le_bytes());
+    }
+
+    #[test]
+    fn file_device_weight_works() {
+        let mut device: FileDevice = serde_json::from_str(
+            r#"{"id":"d","server":"s","filepath":"/tmp/d","capacity":"1KiB"}"#,
+        )
+        .unwrap();
+        assert_eq!(device.weight(), 1024);
+        assert_eq!(device.resolved_weight().unwrap(), 1024);
+
+        device.weight = Weight::Relative(0.5);
+        assert_eq!(device.weight(), 512);
+
+        // 割合指定の場合、`weight()`はファイルシステムを参照しない
+        device.capacity = CapacitySpec::PercentOfFree(50.0);
+        assert_eq!(device.weight(), 0);
+        device.weight = Weight::Absolute(7);
+        assert_eq!(device.weight(), 7);
+        assert_eq!(device.resolved_weight().unwrap(), 7);
     }
 }
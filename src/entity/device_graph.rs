@@ -0,0 +1,437 @@
+//! デバイス群が構成する木構造。
+//!
+//! 仮想デバイスを親、その子デバイス群を子とするグラフを構築し、
+//! 循環や存在しない子デバイスへの参照のような、木構造としての不整合を検出する。
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::entity::device::{Device, DeviceId};
+use crate::{ErrorKind, Result};
+
+/// デバイス群が構成するグラフ。
+#[derive(Debug, Clone)]
+pub struct DeviceGraph<'a> {
+    devices: BTreeMap<&'a DeviceId, &'a Device>,
+    parents: BTreeMap<&'a DeviceId, BTreeSet<&'a DeviceId>>,
+}
+impl<'a> DeviceGraph<'a> {
+    /// 新しい`DeviceGraph`インスタンスを生成する。
+    ///
+    /// 同じIDを持つデバイスが複数含まれる場合には、後のものが優先される。
+    pub fn new<I>(devices: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Device>,
+    {
+        let devices = devices
+            .into_iter()
+            .map(|d| (d.id(), d))
+            .collect::<BTreeMap<_, _>>();
+        let mut parents = BTreeMap::<_, BTreeSet<_>>::new();
+        for d in devices.values() {
+            if let Device::Virtual(ref v) = **d {
+                for child in &v.children {
+                    parents.entry(child).or_default().insert(&v.id);
+                }
+            }
+        }
+        DeviceGraph { devices, parents }
+    }
+
+    /// 指定されたIDを持つデバイスを返す。
+    pub fn get(&self, id: &DeviceId) -> Option<&'a Device> {
+        self.devices.get(id).cloned()
+    }
+
+    /// 指定されたデバイスを子に持つ仮想デバイス群のIDを返す。
+    ///
+    /// 木構造が正しければ、要素数は高々一つとなる。
+    pub fn parents(&self, id: &DeviceId) -> Vec<&'a DeviceId> {
+        self.parents
+            .get(id)
+            .map(|p| p.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 指定されたデバイスの祖先（親、親の親、...）にあたる仮想デバイス群のIDを返す。
+    ///
+    /// 指定されたデバイス自身は含まれない。
+    pub fn ancestors(&self, id: &DeviceId) -> BTreeSet<&'a DeviceId> {
+        let mut ancestors = BTreeSet::new();
+        let mut stack = self.parents(id);
+        while let Some(parent) = stack.pop() {
+            if ancestors.insert(parent) {
+                stack.extend(self.parents(parent));
+            }
+        }
+        ancestors
+    }
+
+    /// 親を持たないデバイス群（木の根）を返す。
+    pub fn roots(&self) -> Vec<&'a Device> {
+        self.devices
+            .iter()
+            .filter(|(id, _)| !self.parents.contains_key(*id))
+            .map(|(_, d)| *d)
+            .collect()
+    }
+
+    /// どの仮想デバイスにも属さない物理デバイス群を返す。
+    pub fn orphans(&self) -> Vec<&'a Device> {
+        self.roots()
+            .into_iter()
+            .filter(|d| !d.is_virtual())
+            .collect()
+    }
+
+    /// 存在しないデバイスを参照している子デバイスの一覧を、`(親のID, 子のID)`の形式で返す。
+    pub fn dangling_children(&self) -> Vec<(&'a DeviceId, &'a DeviceId)> {
+        let mut dangling = Vec::new();
+        for d in self.devices.values() {
+            if let Device::Virtual(ref v) = **d {
+                for child in v.children.iter().filter(|c| !self.devices.contains_key(c)) {
+                    dangling.push((&v.id, child));
+                }
+            }
+        }
+        dangling
+    }
+
+    /// グラフに含まれる循環の一覧を返す。
+    ///
+    /// 各循環は、それを構成するデバイスのIDを親から子の順に並べたものとして表現される。
+    pub fn cycles(&self) -> Vec<Vec<&'a DeviceId>> {
+        let mut marks = BTreeMap::new();
+        let mut cycles = Vec::new();
+        for id in self.devices.keys() {
+            if !marks.contains_key(id) {
+                let mut path = Vec::new();
+                self.find_cycles(id, &mut marks, &mut path, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    /// 指定された仮想デバイスの配下にある物理デバイス群を、IDの昇順で返す。
+    ///
+    /// 物理デバイスが指定された場合には、そのデバイスのみを含む結果が返される。
+    ///
+    /// # Errors
+    ///
+    /// 指定されたデバイスないしその子孫が存在しない場合や、
+    /// 配下に循環が含まれる場合には`ErrorKind::InvalidInput`が返される。
+    pub fn leaves(&self, id: &DeviceId) -> Result<Vec<&'a Device>> {
+        let mut leaves = BTreeMap::new();
+        let mut path = Vec::new();
+        track!(self.collect_leaves(id, &mut path, &mut leaves))?;
+        Ok(leaves.into_values().collect())
+    }
+
+    /// 指定された仮想デバイスから、その配下の各物理デバイスまでの経路を、物理デバイスのIDの昇順で返す。
+    ///
+    /// 各経路は指定されたデバイス自身から始まり、物理デバイスで終わる。
+    /// 配下のデバイス（指定されたデバイス自身は除く）のうち`filter`が`false`を返したものは、
+    /// その子孫ごと結果から除外される。
+    /// 同じ物理デバイスに複数の経路で到達可能な場合には、最初に見つかったものが使用される。
+    ///
+    /// # Errors
+    ///
+    /// `leaves`と同様のエラーに加えて、`filter`が返したエラーがそのまま返される。
+    pub fn leaf_paths<F>(&self, id: &DeviceId, mut filter: F) -> Result<Vec<Vec<&'a Device>>>
+    where
+        F: FnMut(&'a Device) -> Result<bool>,
+    {
+        let device = track_assert_some!(
+            self.get(id),
+            ErrorKind::InvalidInput,
+            "Unknown device: {:?}",
+            id
+        );
+        let mut paths = BTreeMap::new();
+        let mut path = vec![device];
+        track!(self.collect_leaf_paths(&mut path, &mut filter, &mut paths))?;
+        Ok(paths.into_values().collect())
+    }
+
+    fn collect_leaf_paths<F>(
+        &self,
+        path: &mut Vec<&'a Device>,
+        filter: &mut F,
+        paths: &mut BTreeMap<&'a DeviceId, Vec<&'a Device>>,
+    ) -> Result<()>
+    where
+        F: FnMut(&'a Device) -> Result<bool>,
+    {
+        let device = *path.last().expect("Never fails");
+        let v = match *device {
+            Device::Virtual(ref v) => v,
+            _ => {
+                paths.entry(device.id()).or_insert_with(|| path.clone());
+                return Ok(());
+            }
+        };
+        for child in &v.children {
+            let child = track_assert_some!(
+                self.get(child),
+                ErrorKind::InvalidInput,
+                "Unknown device: {:?}",
+                child
+            );
+            track_assert!(
+                !path.iter().any(|d| d.id() == child.id()),
+                ErrorKind::InvalidInput,
+                "Cycle detected: {:?}",
+                path.iter().map(|d| d.id()).collect::<Vec<_>>()
+            );
+            if !track!(filter(child))? {
+                continue;
+            }
+            path.push(child);
+            track!(self.collect_leaf_paths(path, filter, paths))?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    fn find_cycles(
+        &self,
+        id: &'a DeviceId,
+        marks: &mut BTreeMap<&'a DeviceId, bool>,
+        path: &mut Vec<&'a DeviceId>,
+        cycles: &mut Vec<Vec<&'a DeviceId>>,
+    ) {
+        marks.insert(id, false);
+        path.push(id);
+        if let Some(Device::Virtual(v)) = self.devices.get(id).copied() {
+            for child in v.children.iter().filter(|c| self.devices.contains_key(c)) {
+                match marks.get(child) {
+                    None => self.find_cycles(child, marks, path, cycles),
+                    Some(false) => {
+                        let start = path.iter().position(|p| *p == child).expect("Never fails");
+                        cycles.push(path[start..].to_vec());
+                    }
+                    Some(true) => {}
+                }
+            }
+        }
+        path.pop();
+        marks.insert(id, true);
+    }
+
+    fn collect_leaves(
+        &self,
+        id: &DeviceId,
+        path: &mut Vec<&'a DeviceId>,
+        leaves: &mut BTreeMap<&'a DeviceId, &'a Device>,
+    ) -> Result<()> {
+        let device = track_assert_some!(
+            self.get(id),
+            ErrorKind::InvalidInput,
+            "Unknown device: {:?}",
+            id
+        );
+        track_assert!(
+            !path.contains(&device.id()),
+            ErrorKind::InvalidInput,
+            "Cycle detected: {:?}",
+            path
+        );
+        if let Device::Virtual(ref v) = *device {
+            path.push(&v.id);
+            for child in &v.children {
+                track!(self.collect_leaves(child, path, leaves))?;
+            }
+            path.pop();
+        } else {
+            leaves.insert(device.id(), device);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn virtual_device(id: &str, children: &[&str]) -> Device {
+        serde_json::from_value(json!({"virtual": {"id": id, "children": children}})).unwrap()
+    }
+
+    fn memory(id: &str) -> Device {
+        serde_json::from_value(json!({"memory": {"id": id, "server": "s1", "capacity": 100}}))
+            .unwrap()
+    }
+
+    fn ids<'a, I>(devices: I) -> Vec<&'a str>
+    where
+        I: IntoIterator<Item = &'a Device>,
+    {
+        devices.into_iter().map(|d| d.id().as_str()).collect()
+    }
+
+    fn is_invalid<T>(r: Result<T>) -> bool {
+        r.err().map(|e| *e.kind()) == Some(ErrorKind::InvalidInput)
+    }
+
+    #[test]
+    fn roots_and_orphans_work() {
+        let devices = vec![
+            virtual_device("root", &["v1", "m1"]),
+            virtual_device("v1", &["m2"]),
+            virtual_device("empty", &[]),
+            memory("m1"),
+            memory("m2"),
+            memory("m3"),
+        ];
+        let graph = DeviceGraph::new(&devices);
+        assert_eq!(ids(graph.roots()), ["empty", "m3", "root"]);
+        assert_eq!(ids(graph.orphans()), ["m3"]);
+        assert_eq!(graph.parents(&"m2".to_owned()), [&"v1".to_owned()]);
+        assert_eq!(
+            graph
+                .ancestors(&"m2".to_owned())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [&"root".to_owned(), &"v1".to_owned()]
+        );
+        assert!(graph.ancestors(&"root".to_owned()).is_empty());
+    }
+
+    #[test]
+    fn dangling_children_works() {
+        let devices = vec![
+            virtual_device("root", &["v1", "unknown1"]),
+            virtual_device("v1", &["m1", "unknown2"]),
+            memory("m1"),
+        ];
+        let graph = DeviceGraph::new(&devices);
+        let dangling = graph
+            .dangling_children()
+            .into_iter()
+            .map(|(p, c)| (p.as_str(), c.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(dangling, [("root", "unknown1"), ("v1", "unknown2")]);
+
+        // 存在しない子デバイスは根とはみなされない
+        assert_eq!(ids(graph.roots()), ["root"]);
+    }
+
+    #[test]
+    fn cycles_works() {
+        let devices = vec![
+            virtual_device("root", &["v1", "m1"]),
+            virtual_device("v1", &["v2"]),
+            virtual_device("v2", &["v3", "m2"]),
+            virtual_device("v3", &["v1"]),
+            virtual_device("self", &["self"]),
+            memory("m1"),
+            memory("m2"),
+        ];
+        let graph = DeviceGraph::new(&devices);
+        // 循環はIDの昇順に辿った際に見つかった順に並ぶ
+        assert_eq!(
+            graph.cycles(),
+            [
+                vec![&"v1".to_owned(), &"v2".to_owned(), &"v3".to_owned()],
+                vec![&"self".to_owned()],
+            ]
+        );
+
+        // 循環を含まない場合
+        let graph = DeviceGraph::new(&devices[5..]);
+        assert!(graph.cycles().is_empty());
+
+        // 同じデバイスに複数の経路で到達可能なだけでは循環とはみなされない
+        let devices = vec![
+            virtual_device("root", &["v1", "v2"]),
+            virtual_device("v1", &["m1"]),
+            virtual_device("v2", &["m1"]),
+            memory("m1"),
+        ];
+        assert!(DeviceGraph::new(&devices).cycles().is_empty());
+    }
+
+    #[test]
+    fn leaf_paths_works() {
+        let devices = vec![
+            virtual_device("root", &["v1", "v2", "m1"]),
+            virtual_device("v1", &["m3", "m2"]),
+            virtual_device("v2", &["m4"]),
+            memory("m1"),
+            memory("m2"),
+            memory("m3"),
+            memory("m4"),
+        ];
+        let graph = DeviceGraph::new(&devices);
+        let paths = graph.leaf_paths(&"root".to_owned(), |_| Ok(true)).unwrap();
+        assert_eq!(
+            paths
+                .iter()
+                .map(|p| ids(p.iter().cloned()))
+                .collect::<Vec<_>>(),
+            [
+                vec!["root", "m1"],
+                vec!["root", "v1", "m2"],
+                vec!["root", "v1", "m3"],
+                vec!["root", "v2", "m4"],
+            ]
+        );
+
+        // フィルタに弾かれたデバイスは、その子孫ごと除外される
+        let paths = graph
+            .leaf_paths(&"root".to_owned(), |d| Ok(d.id() != "v1"))
+            .unwrap();
+        assert_eq!(
+            paths
+                .iter()
+                .map(|p| ids(p.iter().cloned()))
+                .collect::<Vec<_>>(),
+            [vec!["root", "m1"], vec!["root", "v2", "m4"]]
+        );
+
+        // 物理デバイスが指定された場合
+        let paths = graph.leaf_paths(&"m1".to_owned(), |_| Ok(true)).unwrap();
+        assert_eq!(
+            paths
+                .iter()
+                .map(|p| ids(p.iter().cloned()))
+                .collect::<Vec<_>>(),
+            [vec!["m1"]]
+        );
+
+        // フィルタのエラーはそのまま返される
+        assert!(is_invalid(graph.leaf_paths(&"root".to_owned(), |_| {
+            track_panic!(ErrorKind::InvalidInput)
+        })));
+    }
+
+    #[test]
+    fn leaf_paths_rejects_invalid_graphs() {
+        let devices = vec![
+            virtual_device("root", &["v1", "unknown"]),
+            virtual_device("v1", &["v2", "m1"]),
+            virtual_device("v2", &["v1"]),
+            memory("m1"),
+        ];
+        let graph = DeviceGraph::new(&devices);
+        assert!(is_invalid(
+            graph.leaf_paths(&"nothing".to_owned(), |_| Ok(true))
+        ));
+        assert!(is_invalid(graph.leaf_paths(&"v1".to_owned(), |_| Ok(true))));
+        assert!(is_invalid(
+            graph.leaf_paths(&"root".to_owned(), |_| Ok(true))
+        ));
+
+        // 不正な部分がフィルタで除外される場合にはエラーとならない
+        let paths = graph
+            .leaf_paths(&"v1".to_owned(), |d| Ok(d.id() != "v2"))
+            .unwrap();
+        assert_eq!(
+            paths
+                .iter()
+                .map(|p| ids(p.iter().cloned()))
+                .collect::<Vec<_>>(),
+            [vec!["v1", "m1"]]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! 障害ドメイン関連のエンティティ定義。
+use std::fmt;
+
+use crate::entity::device::Device;
+use crate::entity::server::Server;
+
+/// 障害ドメイン。
+///
+/// 同時に故障し得るデバイス群をまとめる単位を、ゾーン・ラック・ホストの三階層で表現する。
+/// ラック名はゾーン内で一意であればよい。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FailureDomain {
+    /// ゾーン（e.g., データセンタ）。
+    #[serde(default)]
+    pub zone: Option<String>,
+
+    /// ラック。
+    #[serde(default)]
+    pub rack: Option<String>,
+
+    /// ホスト（物理マシン）。
+    #[serde(default)]
+    pub host: Option<String>,
+}
+impl FailureDomain {
+    /// いずれの階層も指定されていないかどうかを判定する。
+    pub fn is_empty(&self) -> bool {
+        self.zone.is_none() && self.rack.is_none() && self.host.is_none()
+    }
+
+    /// 指定されていない階層を`fallback`の値で補った障害ドメインを返す。
+    pub fn or(&self, fallback: &FailureDomain) -> FailureDomain {
+        FailureDomain {
+            zone: self.zone.clone().or_else(|| fallback.zone.clone()),
+            rack: self.rack.clone().or_else(|| fallback.rack.clone()),
+            host: self.host.clone().or_else(|| fallback.host.clone()),
+        }
+    }
+
+    /// 指定階層におけるドメインの識別子を返す。
+    ///
+    /// ラックの識別子はゾーンと組み合わせたものとなる。
+    /// 該当階層が指定されていない場合には`None`が返される。
+    pub fn key(&self, level: FailureDomainLevel) -> Option<String> {
+        match level {
+            FailureDomainLevel::Zone => self.zone.clone(),
+            FailureDomainLevel::Rack => self.rack.as_ref().map(|rack| match self.zone {
+                Some(ref zone) => format!("{}/{}", zone, rack),
+                None => rack.clone(),
+            }),
+            FailureDomainLevel::Host => self.host.clone(),
+        }
+    }
+
+    /// 物理デバイスの障害ドメインを解決する。
+    ///
+    /// デバイス自身に指定された値が優先され、指定されていない階層は`server`の値で補われる。
+    /// ホストがどちらにも指定されていない場合には、サーバのIPアドレスが用いられる。
+    ///
+    /// 仮想デバイスが渡された場合には`None`が返される。
+    pub fn resolve(device: &Device, server: Option<&Server>) -> Option<FailureDomain> {
+        let own = device.failure_domain()?;
+        let domain = match server {
+            None => own.clone(),
+            Some(server) => {
+                let mut domain = own.or(&server.failure_domain);
+                if domain.host.is_none() {
+                    domain.host = Some(server.host.to_string());
+                }
+                domain
+            }
+        };
+        Some(domain)
+    }
+}
+
+/// 障害ドメインの階層。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureDomainLevel {
+    /// ゾーン。
+    Zone,
+
+    /// ラック。
+    Rack,
+
+    /// ホスト。
+    Host,
+}
+impl fmt::Display for FailureDomainLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FailureDomainLevel::Zone => write!(f, "zone"),
+            FailureDomainLevel::Rack => write!(f, "rack"),
+            FailureDomainLevel::Host => write!(f, "host"),
+        }
+    }
+}
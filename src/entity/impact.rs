@@ -0,0 +1,344 @@
+//! 障害影響分析関連のエンティティ定義。
+use std::collections::BTreeSet;
+
+use crate::entity::bucket::{Bucket, BucketId};
+use crate::entity::config::ClusterConfig;
+use crate::entity::device::DeviceId;
+use crate::entity::device_graph::DeviceGraph;
+use crate::entity::server::ServerId;
+
+/// 停止を想定するサーバ・デバイス群。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureScenario {
+    /// 停止するサーバ群。
+    ///
+    /// サーバ上の全ての物理デバイスが停止したものとして扱われる。
+    #[serde(default)]
+    pub servers: BTreeSet<ServerId>,
+
+    /// 停止するデバイス群。
+    ///
+    /// 仮想デバイスが指定された場合には、その配下の全ての物理デバイスが停止したものとして扱われる。
+    #[serde(default)]
+    pub devices: BTreeSet<DeviceId>,
+}
+impl FailureScenario {
+    /// 停止する物理デバイスの集合を返す。
+    ///
+    /// `config`に存在しないデバイスや、木構造が不正な仮想デバイスは無視される。
+    pub fn failed_devices(&self, config: &ClusterConfig) -> BTreeSet<DeviceId> {
+        let graph = DeviceGraph::new(&config.devices);
+        let mut failed = BTreeSet::new();
+        for d in &config.devices {
+            if d.server().is_some_and(|s| self.servers.contains(s)) {
+                failed.insert(d.id().clone());
+            }
+        }
+        for id in &self.devices {
+            if let Ok(leaves) = graph.leaves(id) {
+                failed.extend(leaves.into_iter().map(|d| d.id().clone()));
+            }
+        }
+        failed
+    }
+}
+
+/// 障害影響分析の結果。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImpactReport {
+    /// 影響を受けるバケツ群。
+    ///
+    /// 影響を受けるセグメントを一つも持たないバケツは含まれない。
+    pub buckets: Vec<BucketImpact>,
+}
+impl ImpactReport {
+    /// 影響を受けるバケツが存在しないかどうかを判定する。
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// 読み込みないし書き込みが不可能になるセグメントが存在するかどうかを判定する。
+    pub fn is_disruptive(&self) -> bool {
+        self.buckets
+            .iter()
+            .flat_map(|b| b.segments.iter())
+            .any(|s| !(s.readable && s.writable))
+    }
+}
+
+/// バケツ単位の障害影響。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketImpact {
+    /// バケツのID。
+    pub bucket: BucketId,
+
+    /// 影響を受けるセグメント群。
+    ///
+    /// セグメント番号の昇順に並んでいる。
+    pub segments: Vec<SegmentImpact>,
+}
+
+/// セグメント単位の障害影響。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentImpact {
+    /// セグメント番号。
+    pub segment: u16,
+
+    /// 停止するノードの数。
+    pub lost_nodes: u8,
+
+    /// 停止後も稼働しているノードの数。
+    pub remaining_nodes: u8,
+
+    /// オブジェクトの読み込みが可能かどうか。
+    ///
+    /// `MetadataBucket`では、一つでもノードが残っていれば（`ReadConsistency::Stale`での）読み込みが可能とみなす。
+    /// それ以外のバケツでは、停止したノードがオブジェクトの内容を保持していた最悪の場合を想定し、
+    /// 停止ノード数が`tolerable_faults`以下であれば読み込みが可能とみなす
+    /// （ErasureCodingの場合には、復元に必要な`data_fragment_count`個のフラグメントが残ることを意味する）。
+    /// `HybridBucket`では、`HybridBucket::tolerable_faults`の値が用いられる。
+    pub readable: bool,
+
+    /// オブジェクトの書き込みが可能かどうか。
+    ///
+    /// Raftクラスタの過半数のノードが残っていれば書き込みが可能とみなす。
+    pub writable: bool,
+
+    /// 読み込みと書き込みは可能だが、あと一つでもノードが停止すると、いずれかが不可能になるかどうか。
+    pub at_risk: bool,
+}
+
+/// バケツの各セグメントへの障害影響を分析する。
+///
+/// `segments`はセグメント番号順に並んだ、各セグメントのノード群が割り当てられた物理デバイスのIDのリスト
+/// （e.g., `simulator::Placement::segments`）。
+/// `failed`は停止する物理デバイスの集合（`FailureScenario::failed_devices`参照）。
+pub fn analyze_bucket(
+    bucket: &Bucket,
+    segments: &[Vec<DeviceId>],
+    failed: &BTreeSet<DeviceId>,
+) -> BucketImpact {
+    let (tolerable_faults, is_metadata) = match *bucket {
+        Bucket::Metadata(ref b) => (b.tolerable_faults, true),
+        Bucket::Replicated(ref b) => (b.tolerable_faults, false),
+        Bucket::Dispersed(ref b) => (b.tolerable_faults, false),
+        Bucket::Hybrid(ref b) => (b.tolerable_faults(), false),
+    };
+    let mut impacts = Vec::new();
+    for (i, nodes) in segments.iter().enumerate() {
+        let lost = nodes.iter().filter(|d| failed.contains(*d)).count();
+        if lost == 0 {
+            continue;
+        }
+        let remaining = nodes.len() - lost;
+        let quorum = nodes.len() / 2 + 1;
+        let readable = if is_metadata {
+            remaining > 0
+        } else {
+            lost <= tolerable_faults as usize
+        };
+        let writable = remaining >= quorum;
+        let at_risk = readable
+            && writable
+            && (remaining == quorum || (!is_metadata && lost == tolerable_faults as usize));
+        impacts.push(SegmentImpact {
+            segment: i as u16,
+            lost_nodes: lost as u8,
+            remaining_nodes: remaining as u8,
+            readable,
+            writable,
+            at_risk,
+        });
+    }
+    BucketImpact {
+        bucket: bucket.id().clone(),
+        segments: impacts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn bucket(value: Value) -> Bucket {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// 停止ノード数が`0..=group_size`の各セグメントを分析し、
+    /// 結果を`(停止ノード数, readable, writable, at_risk)`のリストとして返す。
+    fn analyze(bucket: &Bucket) -> Vec<(u8, bool, bool, bool)> {
+        let group_size = usize::from(bucket.device_group_size());
+        let segments = (0..=group_size)
+            .map(|lost| {
+                (0..group_size)
+                    .map(|i| {
+                        if i < lost {
+                            format!("failed{}", i)
+                        } else {
+                            format!("alive{}", i)
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<DeviceId>>>();
+        let failed = (0..group_size).map(|i| format!("failed{}", i)).collect();
+        let impact = analyze_bucket(bucket, &segments, &failed);
+        assert_eq!(impact.bucket, "b");
+        impact
+            .segments
+            .iter()
+            .map(|s| {
+                assert_eq!(usize::from(s.segment), usize::from(s.lost_nodes));
+                assert_eq!(usize::from(s.lost_nodes + s.remaining_nodes), group_size);
+                (s.lost_nodes, s.readable, s.writable, s.at_risk)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn failed_devices_works() {
+        let config: ClusterConfig = serde_json::from_value(json!({"devices": [
+            {"virtual": {"id": "v1", "children": ["m3", "v2"]}},
+            {"virtual": {"id": "v2", "children": ["m4"]}},
+            {"memory": {"id": "m1", "server": "s1", "capacity": 1}},
+            {"memory": {"id": "m2", "server": "s1", "capacity": 1}},
+            {"memory": {"id": "m3", "server": "s2", "capacity": 1}},
+            {"memory": {"id": "m4", "server": "s3", "capacity": 1}}
+        ]}))
+        .unwrap();
+        let failed = |servers: &[&str], devices: &[&str]| {
+            let scenario = FailureScenario {
+                servers: servers.iter().map(|&s| s.to_owned()).collect(),
+                devices: devices.iter().map(|&d| d.to_owned()).collect(),
+            };
+            scenario
+                .failed_devices(&config)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        // サーバは、その上の物理デバイス群に展開される
+        assert_eq!(failed(&["s1"], &[]), ["m1", "m2"]);
+
+        // 仮想デバイスは、その配下の物理デバイス群に展開される
+        assert_eq!(failed(&[], &["v1"]), ["m3", "m4"]);
+        assert_eq!(failed(&[], &["v2"]), ["m4"]);
+        assert_eq!(failed(&[], &["m2"]), ["m2"]);
+
+        assert_eq!(failed(&["s1"], &["v2"]), ["m1", "m2", "m4"]);
+        assert!(failed(&["unknown"], &["unknown"]).is_empty());
+    }
+
+    #[test]
+    fn replicated_bucket_boundaries() {
+        // 3ノード（過半数は2）
+        let b = bucket(json!({"replicated": {"id": "b", "device": "d", "tolerable_faults": 1}}));
+        assert_eq!(
+            analyze(&b),
+            [
+                (1, true, true, true),
+                (2, false, false, false),
+                (3, false, false, false)
+            ]
+        );
+
+        // 5ノード（過半数は3）
+        let b = bucket(json!({"replicated": {"id": "b", "device": "d", "tolerable_faults": 2}}));
+        assert_eq!(
+            analyze(&b),
+            [
+                (1, true, true, false),
+                (2, true, true, true),
+                (3, false, false, false),
+                (4, false, false, false),
+                (5, false, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_bucket_boundaries() {
+        // 一つでもノードが残っていれば読み込み可能
+        let b = bucket(json!({"metadata": {"id": "b", "device": "d", "tolerable_faults": 1}}));
+        assert_eq!(
+            analyze(&b),
+            [
+                (1, true, true, true),
+                (2, true, false, false),
+                (3, false, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn dispersed_bucket_boundaries() {
+        // データ4、パリティ2の計6ノード（過半数は4）
+        let b = bucket(json!({"dispersed": {
+            "id": "b", "device": "d", "tolerable_faults": 2, "data_fragment_count": 4
+        }}));
+        assert_eq!(b.device_group_size(), 6);
+        assert_eq!(
+            &analyze(&b)[..3],
+            [
+                (1, true, true, false),
+                (2, true, true, true),
+                (3, false, false, false)
+            ]
+        );
+
+        // ローカルパリティを含めて8ノード（過半数は5）だが、読み込みの可否は`tolerable_faults`で決まる
+        let b = bucket(json!({"dispersed": {
+            "id": "b", "device": "d", "tolerable_faults": 2, "data_fragment_count": 4,
+            "erasure_coding": {"scheme": {"locally_repairable": {"local_group_size": 2}}}
+        }}));
+        assert_eq!(b.device_group_size(), 8);
+        assert_eq!(
+            &analyze(&b)[..4],
+            [
+                (1, true, true, false),
+                (2, true, true, true),
+                (3, false, true, false),
+                (4, false, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn hybrid_bucket_uses_min_tolerable_faults() {
+        // 複製の故障耐性は1、ErasureCodingの故障耐性は2（データ3、パリティ2の計5ノード、過半数は3）
+        let b = bucket(json!({"hybrid": {
+            "id": "b", "device": "d", "size_threshold": 1024,
+            "replicated_tolerable_faults": 1, "dispersed_tolerable_faults": 2,
+            "data_fragment_count": 3
+        }}));
+        assert_eq!(b.device_group_size(), 5);
+        assert_eq!(
+            &analyze(&b)[..3],
+            [
+                (1, true, true, true),
+                (2, false, true, false),
+                (3, false, false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn report_is_disruptive() {
+        let b = bucket(json!({"replicated": {"id": "b", "device": "d", "tolerable_faults": 1}}));
+        let segments = vec![vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]];
+        let analyze = |failed: &[&str]| {
+            let failed = failed.iter().map(|&d| d.to_owned()).collect();
+            let impact = analyze_bucket(&b, &segments, &failed);
+            let mut report = ImpactReport::default();
+            if !impact.segments.is_empty() {
+                report.buckets.push(impact);
+            }
+            report
+        };
+        assert!(analyze(&[]).is_empty());
+        assert!(!analyze(&["a"]).is_disruptive());
+        assert!(analyze(&["a", "b"]).is_disruptive());
+    }
+}
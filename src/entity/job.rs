@@ -0,0 +1,176 @@
+//! ジョブ関連のエンティティ定義。
+//!
+//! ジョブは、セグメントの移動を伴うような、完了までに時間を要する構成変更を表す。
+use crate::entity::bucket::{BucketId, Redundancy};
+use crate::entity::config::ActorId;
+use crate::entity::device::DeviceId;
+use crate::entity::server::ServerId;
+use crate::time::Seconds;
+
+// FIXME: 構造体にする
+/// ジョブのID。
+pub type JobId = String;
+
+/// ジョブの種類。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// サーバの撤去。
+    ///
+    /// 以下の順に処理が進む:
+    ///
+    /// 1. `JobPhase::Preparing`: サーバ上の全デバイスを`DeviceState::Draining`にする
+    /// 2. `JobPhase::Migrating`: それらのデバイス上の全セグメントを他のデバイスに移動する
+    /// 3. `JobPhase::Finalizing`: デバイス群およびサーバを削除する
+    DecommissionServer(ServerId),
+
+    /// 物理デバイスの交換。
+    ///
+    /// 以下の順に処理が進む:
+    ///
+    /// 1. `JobPhase::Preparing`: 新しいデバイスを登録し、旧デバイスを子に持つ仮想デバイス群の`children`を
+    ///    新しいデバイスに置き換える（これらは一つの構成変更として不可分に行われる）。
+    ///    旧デバイスは`DeviceState::Draining`となる
+    /// 2. `JobPhase::Migrating`: 旧デバイス上の全セグメントを新しいデバイスに移動する
+    /// 3. `JobPhase::Finalizing`: 旧デバイスを削除する
+    ReplaceDevice {
+        /// 交換される旧デバイスのID。
+        old: DeviceId,
+
+        /// 交換後の新しいデバイスのID。
+        new: DeviceId,
+    },
+
+    /// バケツのセグメント数の変更。
+    ///
+    /// 以下の順に処理が進む:
+    ///
+    /// 1. `JobPhase::Preparing`: 新しいセグメント数に基づくセグメント群を割り当てる
+    /// 2. `JobPhase::Migrating`: 旧セグメント群のオブジェクトを新しいセグメント群に移動する。
+    ///    この間、書き込みは新しいセグメント群に対して行われ、
+    ///    読み込みは新しいセグメント群に存在しなければ旧セグメント群に対して行われる
+    /// 3. `JobPhase::Finalizing`: バケツの`segment_count`を更新し、旧セグメント群を削除する
+    ///
+    /// `JobProgress`は旧セグメント単位で数えられる。
+    ReshardBucket {
+        /// 対象バケツのID。
+        bucket: BucketId,
+
+        /// 変更前のセグメント数。
+        from: u16,
+
+        /// 変更後のセグメント数。
+        to: u16,
+    },
+
+    /// バケツの冗長化方式の変更。
+    ///
+    /// 以下の順に処理が進む:
+    ///
+    /// 1. `JobPhase::Preparing`: 新しい冗長化方式に基づくセグメント群を割り当てる
+    /// 2. `JobPhase::Migrating`: 全オブジェクトを新しい冗長化方式で再エンコードし、新しいセグメント群に書き込む。
+    ///    この間、書き込みは新しいセグメント群に対して行われ、
+    ///    読み込みは新しいセグメント群に存在しなければ旧セグメント群に対して行われる
+    /// 3. `JobPhase::Finalizing`: バケツの定義（必要なら種類も）を更新し、旧セグメント群を削除する
+    ///
+    /// `JobProgress`は旧セグメント単位で数えられる。
+    ChangeBucketRedundancy {
+        /// 対象バケツのID。
+        bucket: BucketId,
+
+        /// 変更前の冗長化方式。
+        from: Redundancy,
+
+        /// 変更後の冗長化方式。
+        to: Redundancy,
+    },
+}
+
+/// ジョブの進行段階。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPhase {
+    /// 開始待ち。
+    Pending,
+
+    /// 移動の準備中。
+    Preparing,
+
+    /// セグメントの移動中。
+    Migrating,
+
+    /// 移動完了後の後処理中。
+    Finalizing,
+
+    /// 正常に完了した。
+    Completed,
+
+    /// 失敗した。
+    Failed,
+}
+impl JobPhase {
+    /// ジョブが終了しているかどうかを判定する。
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobPhase::Completed | JobPhase::Failed)
+    }
+}
+
+/// ジョブの進捗。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// 移動対象のセグメント（ノード）の総数。
+    pub total_segments: u64,
+
+    /// 移動が完了したセグメント（ノード）の数。
+    pub migrated_segments: u64,
+}
+impl JobProgress {
+    /// 進捗率をパーセント単位で返す。
+    ///
+    /// 移動対象が存在しない場合には`100.0`を返す。
+    pub fn percent(&self) -> f64 {
+        if self.total_segments == 0 {
+            100.0
+        } else {
+            self.migrated_segments as f64 * 100.0 / self.total_segments as f64
+        }
+    }
+}
+
+/// ジョブの状態。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    /// ID。
+    pub id: JobId,
+
+    /// 種類。
+    pub kind: JobKind,
+
+    /// 進行段階。
+    pub phase: JobPhase,
+
+    /// 進捗。
+    pub progress: JobProgress,
+
+    /// ジョブを開始した主体。
+    #[serde(default)]
+    pub actor: Option<ActorId>,
+
+    /// ジョブの作成時刻（UNIXエポックからの経過秒数）。
+    pub created_at: Seconds,
+
+    /// 状態の最終更新時刻（UNIXエポックからの経過秒数）。
+    pub updated_at: Seconds,
+
+    /// 失敗の理由。
+    ///
+    /// `phase`が`JobPhase::Failed`の場合にのみ値を持つ。
+    #[serde(default)]
+    pub error: Option<String>,
+}
+impl JobStatus {
+    /// ジョブが終了しているかどうかを判定する。
+    pub fn is_finished(&self) -> bool {
+        self.phase.is_finished()
+    }
+}
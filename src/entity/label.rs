@@ -0,0 +1,252 @@
+//! ラベル関連のエンティティ定義。
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Error, ErrorKind, Result};
+
+/// エンティティに付与される任意のキー・値の組の集合。
+///
+/// 所有者や環境、ハードウェア情報のような、frugalos自体は関知しない情報を保持するために使用される。
+pub type Labels = BTreeMap<String, String>;
+
+/// ラベルによるエンティティの選択条件。
+///
+/// 全ての要件を満たすラベル群を持つエンティティが選択される。
+/// 要件が一つも存在しない場合には、全てのエンティティが選択される。
+///
+/// 文字列表現は要件をカンマで区切ったもので、各要件は以下のいずれかの形式となる:
+///
+/// - `key=value`: `key`の値が`value`である
+/// - `key!=value`: `key`の値が`value`ではない（`key`が存在しない場合も含む）
+/// - `key`: `key`が存在する
+/// - `!key`: `key`が存在しない
+///
+/// キーは空であってはならず、`!`で始まってもいけない。
+/// 空文字列は要件を持たない`LabelSelector`として扱われる。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LabelSelector {
+    /// 要件群。
+    pub requirements: Vec<LabelRequirement>,
+}
+impl LabelSelector {
+    /// 全てのエンティティを選択する`LabelSelector`インスタンスを生成する。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 要件を追加する。
+    pub fn require(mut self, requirement: LabelRequirement) -> Self {
+        self.requirements.push(requirement);
+        self
+    }
+
+    /// 要件が一つも存在しないかどうかを判定する。
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+    }
+
+    /// `labels`が全ての要件を満たすかどうかを判定する。
+    pub fn matches(&self, labels: &Labels) -> bool {
+        self.requirements.iter().all(|r| r.matches(labels))
+    }
+}
+impl fmt::Display for LabelSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, r) in self.requirements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", r)?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for LabelSelector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().is_empty() {
+            return Ok(LabelSelector::new());
+        }
+
+        let mut requirements = Vec::new();
+        for r in s.split(',') {
+            requirements.push(track!(r.parse(), "selector={:?}", s)?);
+        }
+        Ok(LabelSelector { requirements })
+    }
+}
+
+/// ラベルに対する要件。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelRequirement {
+    /// キーの値が指定値と等しい。
+    Equals(String, String),
+
+    /// キーの値が指定値と等しくない（キーが存在しない場合も含む）。
+    NotEquals(String, String),
+
+    /// キーが存在する。
+    Exists(String),
+
+    /// キーが存在しない。
+    NotExists(String),
+}
+impl LabelRequirement {
+    /// `labels`が要件を満たすかどうかを判定する。
+    pub fn matches(&self, labels: &Labels) -> bool {
+        match *self {
+            LabelRequirement::Equals(ref k, ref v) => labels.get(k) == Some(v),
+            LabelRequirement::NotEquals(ref k, ref v) => labels.get(k) != Some(v),
+            LabelRequirement::Exists(ref k) => labels.contains_key(k),
+            LabelRequirement::NotExists(ref k) => !labels.contains_key(k),
+        }
+    }
+}
+impl fmt::Display for LabelRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LabelRequirement::Equals(ref k, ref v) => write!(f, "{}={}", k, v),
+            LabelRequirement::NotEquals(ref k, ref v) => write!(f, "{}!={}", k, v),
+            LabelRequirement::Exists(ref k) => write!(f, "{}", k),
+            LabelRequirement::NotExists(ref k) => write!(f, "!{}", k),
+        }
+    }
+}
+impl FromStr for LabelRequirement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let requirement = if let Some(i) = s.find("!=") {
+            LabelRequirement::NotEquals(s[..i].trim().to_owned(), s[i + 2..].trim().to_owned())
+        } else if let Some(i) = s.find('=') {
+            LabelRequirement::Equals(s[..i].trim().to_owned(), s[i + 1..].trim().to_owned())
+        } else if let Some(key) = s.strip_prefix('!') {
+            LabelRequirement::NotExists(key.trim().to_owned())
+        } else {
+            LabelRequirement::Exists(s.to_owned())
+        };
+        let key = match requirement {
+            LabelRequirement::Equals(ref k, _)
+            | LabelRequirement::NotEquals(ref k, _)
+            | LabelRequirement::Exists(ref k)
+            | LabelRequirement::NotExists(ref k) => k,
+        };
+        track_assert!(
+            !key.is_empty(),
+            ErrorKind::InvalidInput,
+            "Empty label key: {:?}",
+            s
+        );
+        track_assert!(
+            !key.starts_with('!'),
+            ErrorKind::InvalidInput,
+            "Label key must not start with '!': {:?}",
+            s
+        );
+        Ok(requirement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<LabelRequirement> {
+        s.parse()
+    }
+
+    fn eq(k: &str, v: &str) -> LabelRequirement {
+        LabelRequirement::Equals(k.to_owned(), v.to_owned())
+    }
+
+    fn ne(k: &str, v: &str) -> LabelRequirement {
+        LabelRequirement::NotEquals(k.to_owned(), v.to_owned())
+    }
+
+    #[test]
+    fn requirement_parses() {
+        assert_eq!(parse("env=prod").unwrap(), eq("env", "prod"));
+        assert_eq!(parse("env!=prod").unwrap(), ne("env", "prod"));
+        assert_eq!(
+            parse("env").unwrap(),
+            LabelRequirement::Exists("env".to_owned())
+        );
+        assert_eq!(
+            parse("!env").unwrap(),
+            LabelRequirement::NotExists("env".to_owned())
+        );
+        assert_eq!(parse("env=").unwrap(), eq("env", ""));
+
+        // 前後の空白は無視される
+        assert_eq!(parse(" env = prod ").unwrap(), eq("env", "prod"));
+        assert_eq!(parse(" env != prod ").unwrap(), ne("env", "prod"));
+        assert_eq!(
+            parse(" ! env ").unwrap(),
+            LabelRequirement::NotExists("env".to_owned())
+        );
+    }
+
+    #[test]
+    fn requirement_rejects_invalid_keys() {
+        assert!(parse("").is_err());
+        assert!(parse("  ").is_err());
+        assert!(parse("!").is_err());
+        assert!(parse("=prod").is_err());
+        assert!(parse("!=prod").is_err());
+        assert!(parse("!env=prod").is_err());
+        assert!(parse("!env!=prod").is_err());
+        assert!(parse("!!env").is_err());
+    }
+
+    #[test]
+    fn selector_parses() {
+        let selector: LabelSelector = "env=prod, tier!=db ,ssd,!retired".parse().unwrap();
+        assert_eq!(
+            selector,
+            LabelSelector::new()
+                .require(eq("env", "prod"))
+                .require(ne("tier", "db"))
+                .require(LabelRequirement::Exists("ssd".to_owned()))
+                .require(LabelRequirement::NotExists("retired".to_owned()))
+        );
+
+        assert!("".parse::<LabelSelector>().unwrap().is_empty());
+        assert!("  ".parse::<LabelSelector>().unwrap().is_empty());
+        assert!("env=prod,".parse::<LabelSelector>().is_err());
+        assert!("env=prod,,ssd".parse::<LabelSelector>().is_err());
+        assert!("env=prod,!ssd=true".parse::<LabelSelector>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let selector = LabelSelector::new()
+            .require(eq("env", "prod"))
+            .require(ne("tier", "db"))
+            .require(LabelRequirement::Exists("ssd".to_owned()))
+            .require(LabelRequirement::NotExists("retired".to_owned()));
+        let s = selector.to_string();
+        assert_eq!(s, "env=prod,tier!=db,ssd,!retired");
+        assert_eq!(s.parse::<LabelSelector>().unwrap(), selector);
+
+        let empty = LabelSelector::new();
+        assert_eq!(empty.to_string().parse::<LabelSelector>().unwrap(), empty);
+    }
+
+    #[test]
+    fn selector_matches() {
+        let labels: Labels = vec![("env", "prod"), ("ssd", "")]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        let matches = |s: &str| s.parse::<LabelSelector>().unwrap().matches(&labels);
+        assert!(matches(""));
+        assert!(matches("env=prod,ssd,!retired"));
+        assert!(matches("tier!=db"));
+        assert!(!matches("env!=prod"));
+        assert!(!matches("env=prod,retired"));
+    }
+}
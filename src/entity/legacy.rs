@@ -0,0 +1,784 @@
+//! 旧形式のエンティティ定義。
+//!
+//! 既存のRPC（e.g., `GetDeviceRpc`）の要求・応答は、ここで定義されている旧形式のエンティティを使用する。
+//! バイナリ形式（bincode）では構造体のフィールドが位置で識別されるため、
+//! 旧形式のエンティティの構造を変更してはいけない。
+//!
+//! 新規に追加されたフィールドを含むエンティティを扱う場合には、
+//! 対応する新しいRPC（e.g., `GetDeviceV2Rpc`）を使用すること。
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use crate::entity::bucket::{self, BucketId, BucketKind, ErasureCoding};
+use crate::entity::device::{
+    self, CapacitySpec, DeviceId, DeviceKind, SegmentAllocationPolicy, Weight,
+};
+use crate::entity::server::{self, ServerId};
+use crate::{Error, ErrorKind};
+
+/// 旧形式のサーバの要約。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerSummary {
+    /// ID。
+    pub id: ServerId,
+}
+impl From<server::ServerSummary> for ServerSummary {
+    fn from(f: server::ServerSummary) -> Self {
+        ServerSummary { id: f.id }
+    }
+}
+
+/// 旧形式のサーバ。
+///
+/// 新形式への変換時には、追加されたフィールドには既定値が使用される。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Server {
+    /// ID。
+    pub id: ServerId,
+
+    /// シーケンス番号。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// ホスト情報。
+    pub host: IpAddr,
+
+    /// ポート番号。
+    pub port: u16,
+}
+impl From<Server> for server::Server {
+    fn from(f: Server) -> Self {
+        server::Server {
+            id: f.id,
+            seqno: f.seqno,
+            host: f.host,
+            port: f.port,
+            failure_domain: Default::default(),
+            labels: Default::default(),
+        }
+    }
+}
+impl From<server::Server> for Server {
+    fn from(f: server::Server) -> Self {
+        Server {
+            id: f.id,
+            seqno: f.seqno,
+            host: f.host,
+            port: f.port,
+        }
+    }
+}
+
+/// 旧形式のデバイスの要約。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSummary {
+    /// デバイスのID。
+    pub id: DeviceId,
+
+    /// デバイスを保持しているサーバ。
+    #[serde(default)]
+    pub server: Option<ServerId>,
+
+    /// デバイスの種類。
+    #[serde(rename = "type")]
+    pub kind: DeviceKind,
+}
+impl From<device::DeviceSummary> for DeviceSummary {
+    fn from(f: device::DeviceSummary) -> Self {
+        DeviceSummary {
+            id: f.id,
+            server: f.server,
+            kind: f.kind,
+        }
+    }
+}
+
+/// 旧形式のデバイス。
+///
+/// 新形式への変換時には、追加されたフィールドには既定値が使用される。
+/// 新形式からの変換は、旧形式で表現できない容量指定を含む場合には失敗する。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Device {
+    /// 仮想デバイス。
+    Virtual(VirtualDevice),
+
+    /// メモリデバイス。
+    Memory(MemoryDevice),
+
+    /// ファイルデバイス。
+    File(FileDevice),
+}
+impl From<Device> for device::Device {
+    fn from(f: Device) -> Self {
+        match f {
+            Device::Virtual(d) => device::Device::Virtual(d.into()),
+            Device::Memory(d) => device::Device::Memory(d.into()),
+            Device::File(d) => device::Device::File(d.into()),
+        }
+    }
+}
+impl TryFrom<device::Device> for Device {
+    type Error = Error;
+
+    fn try_from(f: device::Device) -> Result<Self, Self::Error> {
+        Ok(match f {
+            device::Device::Virtual(d) => Device::Virtual(d.into()),
+            device::Device::Memory(d) => Device::Memory(d.into()),
+            device::Device::File(d) => Device::File(track!(FileDevice::try_from(d))?),
+        })
+    }
+}
+
+/// 旧形式の仮想デバイス。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VirtualDevice {
+    /// ID。
+    pub id: DeviceId,
+
+    /// シーケンス番号。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// オブジェクトの割当比率を決定する際の重み。
+    #[serde(default)]
+    pub weight: Weight,
+
+    /// 子デバイス群。
+    pub children: BTreeSet<DeviceId>,
+
+    /// オブジェクトの割当方針。
+    #[serde(default)]
+    pub policy: SegmentAllocationPolicy,
+}
+impl From<VirtualDevice> for device::VirtualDevice {
+    fn from(f: VirtualDevice) -> Self {
+        device::VirtualDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            state: Default::default(),
+            children: f.children,
+            policy: f.policy,
+            labels: Default::default(),
+        }
+    }
+}
+impl From<device::VirtualDevice> for VirtualDevice {
+    fn from(f: device::VirtualDevice) -> Self {
+        VirtualDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            children: f.children,
+            policy: f.policy,
+        }
+    }
+}
+
+/// 旧形式のメモリデバイス。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryDevice {
+    /// ID。
+    pub id: DeviceId,
+
+    /// シーケンス番号。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// オブジェクトの割当比率を決定する際の重み。
+    #[serde(default)]
+    pub weight: Weight,
+
+    /// デバイスを保持しているサーバ。
+    pub server: ServerId,
+
+    /// 容量（バイト単位）。
+    pub capacity: u64,
+}
+impl From<MemoryDevice> for device::MemoryDevice {
+    fn from(f: MemoryDevice) -> Self {
+        device::MemoryDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            state: Default::default(),
+            server: f.server,
+            capacity: f.capacity,
+            failure_domain: Default::default(),
+            labels: Default::default(),
+        }
+    }
+}
+impl From<device::MemoryDevice> for MemoryDevice {
+    fn from(f: device::MemoryDevice) -> Self {
+        MemoryDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            server: f.server,
+            capacity: f.capacity,
+        }
+    }
+}
+
+/// 旧形式のファイルデバイス。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDevice {
+    /// ID。
+    pub id: DeviceId,
+
+    /// シーケンス番号。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// オブジェクトの割当比率を決定する際の重み。
+    #[serde(default)]
+    pub weight: Weight,
+
+    /// デバイスを保持しているサーバ。
+    pub server: ServerId,
+
+    /// 容量（バイト単位）。
+    ///
+    /// `0`は`CapacitySpec::default()`を意味する。
+    #[serde(default)]
+    pub capacity: u64,
+
+    /// ファイルパス。
+    pub filepath: PathBuf,
+}
+impl From<FileDevice> for device::FileDevice {
+    fn from(f: FileDevice) -> Self {
+        let capacity = if f.capacity == 0 {
+            CapacitySpec::default()
+        } else {
+            CapacitySpec::Bytes(f.capacity)
+        };
+        device::FileDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            state: Default::default(),
+            server: f.server,
+            capacity,
+            filepath: f.filepath,
+            failure_domain: Default::default(),
+            labels: Default::default(),
+        }
+    }
+}
+impl TryFrom<device::FileDevice> for FileDevice {
+    type Error = Error;
+
+    fn try_from(f: device::FileDevice) -> Result<Self, Self::Error> {
+        let capacity = match f.capacity {
+            CapacitySpec::Bytes(n) => n,
+            c if c == CapacitySpec::default() => 0,
+            c => track_panic!(
+                ErrorKind::InvalidInput,
+                "Capacity {:?} cannot be represented in the legacy format: device={:?}",
+                c.to_string(),
+                f.id
+            ),
+        };
+        Ok(FileDevice {
+            id: f.id,
+            seqno: f.seqno,
+            weight: f.weight,
+            server: f.server,
+            capacity,
+            filepath: f.filepath,
+        })
+    }
+}
+
+/// 旧形式のバケツの要約。
+///
+/// 旧形式では`BucketKind::Hybrid`を表現できないため、
+/// 旧形式の一覧には`HybridBucket`の要約を含めてはいけない。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BucketSummary {
+    /// バケツのID。
+    pub id: BucketId,
+
+    /// バケツの種類。
+    #[serde(rename = "type")]
+    pub kind: BucketKind,
+
+    /// バケツが使用しているデバイス。
+    pub device: DeviceId,
+}
+impl From<bucket::BucketSummary> for BucketSummary {
+    fn from(f: bucket::BucketSummary) -> Self {
+        BucketSummary {
+            id: f.id,
+            kind: f.kind,
+            device: f.device,
+        }
+    }
+}
+
+/// 旧形式のバケツ。
+///
+/// 新形式への変換時には、追加されたフィールドには既定値が使用される。
+/// 新形式からの変換は、`HybridBucket`や既定値以外のErasureCodingの設定を含む場合には失敗する。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    /// メタデータ用バケツ。
+    Metadata(MetadataBucket),
+
+    /// 複製による冗長化を行うバケツ。
+    Replicated(ReplicatedBucket),
+
+    /// ErasureCodingによる冗長化を行うバケツ。
+    Dispersed(DispersedBucket),
+}
+impl From<Bucket> for bucket::Bucket {
+    fn from(f: Bucket) -> Self {
+        match f {
+            Bucket::Metadata(b) => bucket::Bucket::Metadata(b.into()),
+            Bucket::Replicated(b) => bucket::Bucket::Replicated(b.into()),
+            Bucket::Dispersed(b) => bucket::Bucket::Dispersed(b.into()),
+        }
+    }
+}
+impl TryFrom<bucket::Bucket> for Bucket {
+    type Error = Error;
+
+    fn try_from(f: bucket::Bucket) -> Result<Self, Self::Error> {
+        Ok(match f {
+            bucket::Bucket::Metadata(b) => Bucket::Metadata(b.into()),
+            bucket::Bucket::Replicated(b) => Bucket::Replicated(b.into()),
+            bucket::Bucket::Dispersed(b) => {
+                Bucket::Dispersed(track!(DispersedBucket::try_from(b))?)
+            }
+            bucket::Bucket::Hybrid(b) => track_panic!(
+                ErrorKind::InvalidInput,
+                "Hybrid bucket cannot be represented in the legacy format: bucket={:?}",
+                b.id
+            ),
+        })
+    }
+}
+
+/// 旧形式のメタデータ用のバケツ。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataBucket {
+    /// バケツのID。
+    pub id: BucketId,
+
+    /// バケツのシーケンス番号（登録番号）。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// バケツが使用するデバイス。
+    pub device: DeviceId,
+
+    /// バケツのセグメント数。
+    #[serde(default)]
+    pub segment_count: u32,
+
+    /// 故障耐性数。
+    pub tolerable_faults: u32,
+}
+impl From<MetadataBucket> for bucket::MetadataBucket {
+    fn from(f: MetadataBucket) -> Self {
+        bucket::MetadataBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+            quota: Default::default(),
+            defaults: Default::default(),
+            lifecycle_rules: Vec::new(),
+            labels: Default::default(),
+        }
+    }
+}
+impl From<bucket::MetadataBucket> for MetadataBucket {
+    fn from(f: bucket::MetadataBucket) -> Self {
+        MetadataBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+        }
+    }
+}
+
+/// 旧形式の複製による冗長化を行うバケツ。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicatedBucket {
+    /// バケツのID。
+    pub id: BucketId,
+
+    /// バケツのシーケンス番号（登録番号）。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// バケツが使用するデバイス。
+    pub device: DeviceId,
+
+    /// バケツのセグメント数。
+    #[serde(default)]
+    pub segment_count: u32,
+
+    /// 故障耐性数。
+    pub tolerable_faults: u32,
+}
+impl From<ReplicatedBucket> for bucket::ReplicatedBucket {
+    fn from(f: ReplicatedBucket) -> Self {
+        bucket::ReplicatedBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+            quota: Default::default(),
+            defaults: Default::default(),
+            lifecycle_rules: Vec::new(),
+            labels: Default::default(),
+        }
+    }
+}
+impl From<bucket::ReplicatedBucket> for ReplicatedBucket {
+    fn from(f: bucket::ReplicatedBucket) -> Self {
+        ReplicatedBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+        }
+    }
+}
+
+/// 旧形式のErasureCodingによる冗長化を行うバケツ。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DispersedBucket {
+    /// バケツのID。
+    pub id: BucketId,
+
+    /// バケツのシーケンス番号（登録番号）。
+    #[serde(default)]
+    pub seqno: u32,
+
+    /// バケツが使用するデバイス。
+    pub device: DeviceId,
+
+    /// バケツのセグメント数。
+    #[serde(default)]
+    pub segment_count: u32,
+
+    /// 故障耐性数。
+    pub tolerable_faults: u32,
+
+    /// ErasureCodingのデータフラグメント数。
+    pub data_fragment_count: u32,
+}
+impl From<DispersedBucket> for bucket::DispersedBucket {
+    fn from(f: DispersedBucket) -> Self {
+        bucket::DispersedBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+            data_fragment_count: f.data_fragment_count,
+            erasure_coding: Default::default(),
+            quota: Default::default(),
+            defaults: Default::default(),
+            lifecycle_rules: Vec::new(),
+            labels: Default::default(),
+        }
+    }
+}
+impl TryFrom<bucket::DispersedBucket> for DispersedBucket {
+    type Error = Error;
+
+    fn try_from(f: bucket::DispersedBucket) -> Result<Self, Self::Error> {
+        // 旧形式のクライアントは既定の設定で符号化されていると見做すため、それ以外は拒否する
+        track_assert_eq!(
+            f.erasure_coding,
+            ErasureCoding::default(),
+            ErrorKind::InvalidInput,
+            "Erasure coding settings cannot be represented in the legacy format: bucket={:?}",
+            f.id
+        );
+        Ok(DispersedBucket {
+            id: f.id,
+            seqno: f.seqno,
+            device: f.device,
+            segment_count: f.segment_count,
+            tolerable_faults: f.tolerable_faults,
+            data_fragment_count: f.data_fragment_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytecodec::bincode_codec::{BincodeDecoder, BincodeEncoder};
+    use bytecodec::{DecodeExt, EncodeExt};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use super::*;
+    use crate::Result;
+
+    // 以下のバイト列は、このモジュールの追加前のlibfrugalosでエンコードしたもの
+
+    const FILE_DEVICE: &[u8] = &[
+        2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 102, 48, 3, 0, 0, 0, 1, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0,
+        0, 2, 0, 0, 0, 0, 0, 0, 0, 115, 48, 0, 4, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 47,
+        100,
+    ];
+    const MEMORY_DEVICE: &[u8] = &[
+        1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 109, 48, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0,
+        0, 115, 48, 0, 2, 0, 0, 0, 0, 0, 0,
+    ];
+    const VIRTUAL_DEVICE: &[u8] = &[
+        0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 118, 48, 2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 224,
+        63, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 102, 48, 2, 0, 0, 0, 0, 0, 0, 0, 109,
+        48, 1, 0, 0, 0,
+    ];
+    const DEVICE_SUMMARIES: &[u8] = &[
+        0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 102, 48, 1, 2, 0, 0, 0, 0, 0,
+        0, 0, 115, 48, 2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 118, 48, 0, 0, 0, 0, 0,
+    ];
+    const SERVER: &[u8] = &[
+        2, 0, 0, 0, 0, 0, 0, 0, 115, 48, 4, 0, 0, 0, 0, 0, 0, 0, 127, 0, 0, 1, 184, 11,
+    ];
+    const SERVER_SUMMARIES: &[u8] = &[
+        0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 115, 48,
+    ];
+    const DISPERSED_BUCKET: &[u8] = &[
+        2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 98, 48, 5, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 118, 48,
+        10, 0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0,
+    ];
+    const METADATA_BUCKET: &[u8] = &[
+        0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 98, 49, 6, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 118, 48, 1,
+        0, 0, 0, 1, 0, 0, 0,
+    ];
+    const BUCKET_SUMMARIES: &[u8] = &[
+        0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 98, 48, 2, 0, 0, 0, 2, 0, 0, 0,
+        0, 0, 0, 0, 118, 48, 2, 0, 0, 0, 0, 0, 0, 0, 98, 49, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0,
+        118, 48,
+    ];
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> T {
+        BincodeDecoder::<T>::default()
+            .decode_from_bytes(bytes)
+            .unwrap()
+    }
+
+    fn encode<T: Serialize>(item: T) -> Vec<u8> {
+        BincodeEncoder::<T>::default()
+            .encode_into_bytes(item)
+            .unwrap()
+    }
+
+    fn legacy_file() -> FileDevice {
+        if let Device::File(d) = decode(FILE_DEVICE) {
+            d
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn baseline_devices_decode() {
+        let file: Device = decode(FILE_DEVICE);
+        assert_eq!(
+            file,
+            Device::File(FileDevice {
+                id: "f0".to_owned(),
+                seqno: 3,
+                weight: Weight::Absolute(7),
+                server: "s0".to_owned(),
+                capacity: 1024,
+                filepath: "/d".into(),
+            })
+        );
+        assert_eq!(encode(file), FILE_DEVICE);
+
+        let memory: Device = decode(MEMORY_DEVICE);
+        assert_eq!(
+            memory,
+            Device::Memory(MemoryDevice {
+                id: "m0".to_owned(),
+                seqno: 1,
+                weight: Weight::Auto,
+                server: "s0".to_owned(),
+                capacity: 512,
+            })
+        );
+        assert_eq!(encode(memory), MEMORY_DEVICE);
+
+        let virt: Device = decode(VIRTUAL_DEVICE);
+        assert_eq!(
+            virt,
+            Device::Virtual(VirtualDevice {
+                id: "v0".to_owned(),
+                seqno: 2,
+                weight: Weight::Relative(0.5),
+                children: ["f0", "m0"].iter().map(|&c| c.to_owned()).collect(),
+                policy: SegmentAllocationPolicy::Scatter,
+            })
+        );
+        assert_eq!(encode(virt), VIRTUAL_DEVICE);
+    }
+
+    #[test]
+    fn baseline_servers_decode() {
+        let server: Server = decode(SERVER);
+        assert_eq!(
+            server,
+            Server {
+                id: "s0".to_owned(),
+                seqno: 4,
+                host: "127.0.0.1".parse().unwrap(),
+                port: 3000,
+            }
+        );
+        assert_eq!(encode(server.clone()), SERVER);
+        assert_eq!(Server::from(server::Server::from(server.clone())), server);
+    }
+
+    #[test]
+    fn baseline_buckets_decode() {
+        let dispersed: Bucket = decode(DISPERSED_BUCKET);
+        assert_eq!(
+            dispersed,
+            Bucket::Dispersed(DispersedBucket {
+                id: "b0".to_owned(),
+                seqno: 5,
+                device: "v0".to_owned(),
+                segment_count: 10,
+                tolerable_faults: 2,
+                data_fragment_count: 4,
+            })
+        );
+        assert_eq!(encode(dispersed), DISPERSED_BUCKET);
+
+        let metadata: Bucket = decode(METADATA_BUCKET);
+        assert_eq!(
+            metadata,
+            Bucket::Metadata(MetadataBucket {
+                id: "b1".to_owned(),
+                seqno: 6,
+                device: "v0".to_owned(),
+                segment_count: 1,
+                tolerable_faults: 1,
+            })
+        );
+        assert_eq!(encode(metadata), METADATA_BUCKET);
+    }
+
+    #[test]
+    fn baseline_summaries_decode() {
+        let servers: Result<Vec<ServerSummary>> = decode(SERVER_SUMMARIES);
+        let servers = servers.unwrap();
+        assert_eq!(
+            servers,
+            [ServerSummary {
+                id: "s0".to_owned()
+            }]
+        );
+        assert_eq!(encode(Ok::<_, Error>(servers)), SERVER_SUMMARIES);
+
+        let devices: Result<Vec<DeviceSummary>> = decode(DEVICE_SUMMARIES);
+        let devices = devices.unwrap();
+        assert_eq!(
+            devices,
+            [
+                DeviceSummary {
+                    id: "f0".to_owned(),
+                    server: Some("s0".to_owned()),
+                    kind: DeviceKind::File,
+                },
+                DeviceSummary {
+                    id: "v0".to_owned(),
+                    server: None,
+                    kind: DeviceKind::Virtual,
+                },
+            ]
+        );
+        assert_eq!(encode(Ok::<_, Error>(devices)), DEVICE_SUMMARIES);
+
+        let buckets: Result<Vec<BucketSummary>> = decode(BUCKET_SUMMARIES);
+        let buckets = buckets.unwrap();
+        assert_eq!(
+            buckets,
+            [
+                BucketSummary {
+                    id: "b0".to_owned(),
+                    kind: BucketKind::Dispersed,
+                    device: "v0".to_owned(),
+                },
+                BucketSummary {
+                    id: "b1".to_owned(),
+                    kind: BucketKind::Metadata,
+                    device: "v0".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(encode(Ok::<_, Error>(buckets)), BUCKET_SUMMARIES);
+    }
+
+    #[test]
+    fn device_conversion_works() {
+        let legacy: Device = decode(FILE_DEVICE);
+        let device = device::Device::from(legacy.clone());
+        if let device::Device::File(ref d) = device {
+            assert_eq!(d.capacity, CapacitySpec::Bytes(1024));
+            assert_eq!(d.state, device::DeviceState::Active);
+        } else {
+            panic!("{:?}", device);
+        }
+        assert_eq!(Device::try_from(device).unwrap(), legacy);
+
+        // 容量`0`は既定値として扱われる
+        let mut file = FileDevice::try_from(device::FileDevice::from(FileDevice {
+            capacity: 0,
+            ..legacy_file()
+        }))
+        .unwrap();
+        assert_eq!(file.capacity, 0);
+
+        // 割合による容量指定は旧形式では表現できない
+        let mut d = device::FileDevice::from(legacy_file());
+        d.capacity = CapacitySpec::PercentOfFilesystem(80.0);
+        assert!(FileDevice::try_from(d).is_err());
+
+        file.capacity = 10;
+        assert_eq!(
+            device::FileDevice::from(file).capacity,
+            CapacitySpec::Bytes(10)
+        );
+    }
+
+    #[test]
+    fn bucket_conversion_works() {
+        let legacy: Bucket = decode(DISPERSED_BUCKET);
+        let bucket = bucket::Bucket::from(legacy.clone());
+        assert_eq!(bucket.kind(), BucketKind::Dispersed);
+        assert!(bucket.labels().is_empty());
+        assert_eq!(Bucket::try_from(bucket.clone()).unwrap(), legacy);
+
+        // 既定値以外のErasureCodingの設定は旧形式では表現できない
+        let mut lrc = bucket;
+        if let bucket::Bucket::Dispersed(ref mut b) = lrc {
+            b.erasure_coding.scheme = bucket::ErasureCodingScheme::LocallyRepairable {
+                local_group_size: 2,
+            };
+        }
+        assert!(Bucket::try_from(lrc).is_err());
+    }
+}
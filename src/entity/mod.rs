@@ -1,6 +1,14 @@
 //! エンティティ定義。
 pub mod bucket;
+pub mod config;
 pub mod device;
+pub mod device_graph;
+pub mod failure_domain;
+pub mod impact;
+pub mod job;
+pub mod label;
+pub mod legacy;
 pub mod node;
 pub mod object;
 pub mod server;
+pub mod validation;
@@ -1,6 +1,9 @@
 //! サーバ関連のエンティティ定義。
 use std::net::{IpAddr, SocketAddr};
 
+use crate::entity::failure_domain::FailureDomain;
+use crate::entity::label::Labels;
+
 // FIXME: 構造体にする
 /// サーバのID。
 pub type ServerId = String;
@@ -10,10 +13,14 @@ pub type ServerId = String;
 pub struct ServerSummary {
     /// ID。
     pub id: ServerId,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// サーバ。
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Server {
     /// ID。
     pub id: ServerId,
@@ -29,6 +36,16 @@ pub struct Server {
 
     /// ポート番号。
     pub port: u16,
+
+    /// サーバが属する障害ドメイン。
+    ///
+    /// サーバ上の物理デバイス群の障害ドメインの既定値として使用される。
+    #[serde(default)]
+    pub failure_domain: FailureDomain,
+
+    /// ラベル群。
+    #[serde(default)]
+    pub labels: Labels,
 }
 impl Server {
     /// 新しい`Server`インスタンスを生成する。
@@ -38,6 +55,8 @@ impl Server {
             seqno: 0,
             host: addr.ip(),
             port: addr.port(),
+            failure_domain: FailureDomain::default(),
+            labels: Labels::new(),
         }
     }
 
@@ -45,6 +64,7 @@ impl Server {
     pub fn to_summary(&self) -> ServerSummary {
         ServerSummary {
             id: self.id.clone(),
+            labels: self.labels.clone(),
         }
     }
 
@@ -61,6 +81,8 @@ impl Default for Server {
             seqno: 0,
             host: From::from([0, 0, 0, 0]),
             port: 0,
+            failure_domain: FailureDomain::default(),
+            labels: Labels::new(),
         }
     }
 }
@@ -0,0 +1,733 @@
+//! エンティティの妥当性検証。
+//!
+//! ここで行われるのは、サーバに問い合わせることなく実施可能な検証のみである。
+//! ファイルパスへの到達可能性のようにサーバ側でしか確認できない項目は、
+//! `schema::config`の`Validate*Rpc`群によって検証される。
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::entity::bucket::{Bucket, ErasureCoding, ErasureCodingScheme};
+use crate::entity::config::{ClusterConfig, ConfigEntityId};
+use crate::entity::device::{CapacitySpec, Device, Weight};
+use crate::entity::device_graph::DeviceGraph;
+use crate::entity::failure_domain::FailureDomain;
+use crate::entity::server::Server;
+
+/// 検証によって見つかった問題。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Problem {
+    /// 問題のあるエンティティ。
+    pub target: ConfigEntityId,
+
+    /// 問題のあるフィールドの名前。
+    ///
+    /// 特定のフィールドに起因しない問題の場合には`None`となる。
+    #[serde(default)]
+    pub field: Option<String>,
+
+    /// 問題の説明。
+    pub message: String,
+
+    /// 問題の深刻度。
+    #[serde(default)]
+    pub severity: Severity,
+}
+impl Problem {
+    /// 新しい`Problem`インスタンスを生成する。
+    ///
+    /// 深刻度は`Severity::Error`となる。
+    pub fn new<M: Into<String>>(target: ConfigEntityId, field: Option<&str>, message: M) -> Self {
+        Problem {
+            target,
+            field: field.map(|f| f.to_owned()),
+            message: message.into(),
+            severity: Severity::Error,
+        }
+    }
+
+    /// 深刻度が`Severity::Warning`の`Problem`インスタンスを生成する。
+    pub fn warning<M: Into<String>>(
+        target: ConfigEntityId,
+        field: Option<&str>,
+        message: M,
+    ) -> Self {
+        Problem {
+            severity: Severity::Warning,
+            ..Problem::new(target, field, message)
+        }
+    }
+
+    /// 構成の適用を妨げる問題かどうかを返す。
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.severity == Severity::Warning {
+            write!(f, "warning: ")?;
+        }
+        if let Some(ref field) = self.field {
+            write!(f, "{}: {}: {}", self.target, field, self.message)
+        } else {
+            write!(f, "{}: {}", self.target, self.message)
+        }
+    }
+}
+
+/// 問題の深刻度。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    /// 構成の適用を妨げる問題。
+    #[default]
+    Error,
+
+    /// 構成の適用は可能だが、意図しない設定の可能性がある問題。
+    Warning,
+}
+
+/// サーバ単体の妥当性を検証する。
+pub fn validate_server(server: &Server) -> Vec<Problem> {
+    let target = ConfigEntityId::Server(server.id.clone());
+    let mut problems = Vec::new();
+    if server.id.is_empty() {
+        problems.push(Problem::new(
+            target.clone(),
+            Some("id"),
+            "must not be empty",
+        ));
+    }
+    if server.port == 0 {
+        problems.push(Problem::new(target, Some("port"), "must not be zero"));
+    }
+    problems
+}
+
+/// デバイス単体の妥当性を検証する。
+pub fn validate_device(device: &Device) -> Vec<Problem> {
+    let target = ConfigEntityId::Device(device.id().clone());
+    let mut problems = Vec::new();
+    if device.id().is_empty() {
+        problems.push(Problem::new(
+            target.clone(),
+            Some("id"),
+            "must not be empty",
+        ));
+    }
+    if let Some(server) = device.server() {
+        if server.is_empty() {
+            problems.push(Problem::new(
+                target.clone(),
+                Some("server"),
+                "must not be empty",
+            ));
+        }
+    }
+
+    let weight = match *device {
+        Device::Virtual(ref d) => &d.weight,
+        Device::Memory(ref d) => &d.weight,
+        Device::File(ref d) => &d.weight,
+    };
+    if let Weight::Relative(r) = *weight {
+        if !(r.is_finite() && r >= 0.0) {
+            problems.push(Problem::new(
+                target.clone(),
+                Some("weight"),
+                format!(
+                    "relative weight must be a non-negative finite number: {}",
+                    r
+                ),
+            ));
+        }
+    }
+
+    match *device {
+        Device::Virtual(ref d) => {
+            if d.children.is_empty() {
+                problems.push(Problem::new(target, Some("children"), "must not be empty"));
+            } else if d.children.contains(&d.id) {
+                problems.push(Problem::new(
+                    target,
+                    Some("children"),
+                    "must not contain the device itself",
+                ));
+            }
+        }
+        Device::Memory(ref d) => {
+            if d.capacity == 0 {
+                problems.push(Problem::new(target, Some("capacity"), "must not be zero"));
+            }
+        }
+        Device::File(ref d) => {
+            if d.filepath.file_name().is_none() || d.filepath.parent().is_none() {
+                problems.push(Problem::new(
+                    target.clone(),
+                    Some("filepath"),
+                    format!(
+                        "must be a file path with a parent directory: {:?}",
+                        d.filepath
+                    ),
+                ));
+            }
+            match d.capacity {
+                CapacitySpec::Bytes(0) => {
+                    problems.push(Problem::new(target, Some("capacity"), "must not be zero"));
+                }
+                CapacitySpec::PercentOfFilesystem(p) | CapacitySpec::PercentOfFree(p)
+                    if !(p > 0.0 && p <= 100.0) =>
+                {
+                    problems.push(Problem::new(
+                        target,
+                        Some("capacity"),
+                        format!("percentage must be in the range (0, 100]: {}", p),
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+    problems
+}
+
+/// バケツ単体の妥当性を検証する。
+pub fn validate_bucket(bucket: &Bucket) -> Vec<Problem> {
+    let target = ConfigEntityId::Bucket(bucket.id().clone());
+    let mut problems = Vec::new();
+    if bucket.id().is_empty() {
+        problems.push(Problem::new(
+            target.clone(),
+            Some("id"),
+            "must not be empty",
+        ));
+    }
+    if bucket.device().is_empty() {
+        problems.push(Problem::new(
+            target.clone(),
+            Some("device"),
+            "must not be empty",
+        ));
+    }
+
+    let (segment_count, erasure_coding) = match *bucket {
+        Bucket::Metadata(ref b) => (b.segment_count, None),
+        Bucket::Replicated(ref b) => (b.segment_count, None),
+        Bucket::Dispersed(ref b) => (
+            b.segment_count,
+            Some((b.data_fragment_count, &b.erasure_coding)),
+        ),
+        Bucket::Hybrid(ref b) => (
+            b.segment_count,
+            Some((b.data_fragment_count, &b.erasure_coding)),
+        ),
+    };
+    if segment_count > u32::from(u16::MAX) {
+        problems.push(Problem::new(
+            target.clone(),
+            Some("segment_count"),
+            format!("must be at most {}: {}", u16::MAX, segment_count),
+        ));
+    }
+    if let Bucket::Hybrid(ref b) = *bucket {
+        if b.size_threshold == 0 {
+            problems.push(Problem::new(
+                target.clone(),
+                Some("size_threshold"),
+                "must not be zero",
+            ));
+        }
+    }
+    if let Some((data_fragment_count, ec)) = erasure_coding {
+        if data_fragment_count == 0 {
+            problems.push(Problem::new(
+                target.clone(),
+                Some("data_fragment_count"),
+                "must not be zero",
+            ));
+        }
+        if let ErasureCodingScheme::LocallyRepairable { local_group_size } = ec.scheme {
+            if local_group_size == 0 || local_group_size > data_fragment_count {
+                problems.push(Problem::new(
+                    target.clone(),
+                    Some("erasure_coding.scheme.local_group_size"),
+                    format!(
+                        "must be in the range [1, data_fragment_count({})]: {}",
+                        data_fragment_count, local_group_size
+                    ),
+                ));
+            }
+        }
+        if let Some(alignment) = ec.fragment_alignment {
+            if !alignment.is_power_of_two() {
+                problems.push(Problem::new(
+                    target.clone(),
+                    Some("erasure_coding.fragment_alignment"),
+                    format!("must be a power of two: {}", alignment),
+                ));
+            }
+        }
+    }
+
+    for (i, rule) in bucket.lifecycle_rules().iter().enumerate() {
+        if rule.expiration_days == 0 {
+            problems.push(Problem::new(
+                target.clone(),
+                Some(&format!("lifecycle_rules[{}].expiration_days", i)),
+                "must not be zero",
+            ));
+        }
+    }
+
+    let device_group_size = device_group_size(bucket);
+    if device_group_size > u64::from(u8::MAX) {
+        problems.push(Problem::new(
+            target,
+            None,
+            format!(
+                "device group size must be at most {}: {}",
+                u8::MAX,
+                device_group_size
+            ),
+        ));
+    }
+    problems
+}
+
+/// クラスタ全体の構成情報の妥当性を検証する。
+///
+/// 個々のエンティティに対する検証に加えて、
+/// IDの重複や存在しないエンティティへの参照のような、エンティティ間の整合性も検証する。
+/// 仮想デバイス群が木構造を成しているかどうかや、
+/// どの仮想デバイスにもバケツにも使われていない物理デバイスの有無は`DeviceGraph`を用いて検証される。
+/// なお、使われていない物理デバイスは`Severity::Warning`の問題として報告される。
+pub fn validate_config(config: &ClusterConfig) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    problems.extend(config.servers.iter().flat_map(validate_server));
+    problems.extend(config.devices.iter().flat_map(validate_device));
+    problems.extend(config.buckets.iter().flat_map(validate_bucket));
+
+    let mut servers = BTreeSet::new();
+    for s in &config.servers {
+        if !servers.insert(&s.id) {
+            let target = ConfigEntityId::Server(s.id.clone());
+            problems.push(Problem::new(target, Some("id"), "duplicated"));
+        }
+    }
+    let mut devices = BTreeSet::new();
+    for d in &config.devices {
+        if !devices.insert(d.id()) {
+            let target = ConfigEntityId::Device(d.id().clone());
+            problems.push(Problem::new(target, Some("id"), "duplicated"));
+        }
+    }
+    let mut buckets = BTreeSet::new();
+    for b in &config.buckets {
+        if !buckets.insert(b.id()) {
+            let target = ConfigEntityId::Bucket(b.id().clone());
+            problems.push(Problem::new(target, Some("id"), "duplicated"));
+        }
+    }
+
+    let mut filepaths = BTreeMap::new();
+    for d in &config.devices {
+        let target = ConfigEntityId::Device(d.id().clone());
+        if let Some(server) = d.server() {
+            if !servers.contains(server) {
+                problems.push(Problem::new(
+                    target.clone(),
+                    Some("server"),
+                    format!("unknown server: {:?}", server),
+                ));
+            }
+        }
+        if let Device::File(ref f) = *d {
+            if let Some(other) = filepaths.insert((&f.server, &f.filepath), &f.id) {
+                problems.push(Problem::new(
+                    target,
+                    Some("filepath"),
+                    format!(
+                        "same path as device {:?} on server {:?}: {:?}",
+                        other, f.server, f.filepath
+                    ),
+                ));
+            }
+        }
+    }
+
+    let graph = DeviceGraph::new(&config.devices);
+    for (parent, child) in graph.dangling_children() {
+        problems.push(Problem::new(
+            ConfigEntityId::Device(parent.clone()),
+            Some("children"),
+            format!("unknown device: {:?}", child),
+        ));
+    }
+    for d in &config.devices {
+        let parents = graph.parents(d.id());
+        if parents.len() > 1 {
+            problems.push(Problem::new(
+                ConfigEntityId::Device(d.id().clone()),
+                None,
+                format!("belongs to multiple virtual devices: {:?}", parents),
+            ));
+        }
+    }
+    for d in graph.orphans() {
+        if !config.buckets.iter().any(|b| b.device() == d.id()) {
+            problems.push(Problem::warning(
+                ConfigEntityId::Device(d.id().clone()),
+                None,
+                "not used by any virtual device or bucket",
+            ));
+        }
+    }
+    // 自己参照は`validate_device`で検出済み
+    for cycle in graph.cycles().into_iter().filter(|c| c.len() > 1) {
+        problems.push(Problem::new(
+            ConfigEntityId::Device(cycle[0].clone()),
+            Some("children"),
+            format!("cycle detected: {:?}", cycle),
+        ));
+    }
+
+    for b in &config.buckets {
+        let v = match graph.get(b.device()) {
+            Some(Device::Virtual(v)) => v,
+            _ => continue,
+        };
+        let level = match v.policy.failure_domain_level() {
+            Some(level) => level,
+            None => continue,
+        };
+        // 割当不可な中間の仮想デバイス配下の物理デバイスも、割当の対象にはならない
+        let paths = match graph.leaf_paths(&v.id, |d| Ok(d.state().is_allocatable())) {
+            Ok(paths) => paths,
+            Err(_) => continue, // 木構造の不整合として報告済み
+        };
+
+        let mut domains = BTreeSet::new();
+        for leaf in paths.iter().map(|p| *p.last().expect("Never fails")) {
+            let server = leaf
+                .server()
+                .and_then(|id| config.servers.iter().find(|s| s.id == *id));
+            match FailureDomain::resolve(leaf, server).and_then(|d| d.key(level)) {
+                Some(key) => {
+                    domains.insert(key);
+                }
+                None => problems.push(Problem::new(
+                    ConfigEntityId::Device(leaf.id().clone()),
+                    Some("failure_domain"),
+                    format!("no {} is specified (required by device {:?})", level, v.id),
+                )),
+            }
+        }
+        let required = device_group_size(b);
+        if (domains.len() as u64) < required {
+            problems.push(Problem::new(
+                ConfigEntityId::Bucket(b.id().clone()),
+                Some("device"),
+                format!(
+                    "device group size ({}) exceeds the number of {}s under device {:?} ({})",
+                    required,
+                    level,
+                    v.id,
+                    domains.len()
+                ),
+            ));
+        }
+    }
+
+    for b in &config.buckets {
+        if !devices.contains(b.device()) {
+            problems.push(Problem::new(
+                ConfigEntityId::Bucket(b.id().clone()),
+                Some("device"),
+                format!("unknown device: {:?}", b.device()),
+            ));
+        }
+    }
+    problems
+}
+
+/// `Bucket::device_group_size`と同じ値を、桁溢れさせずに計算する。
+fn device_group_size(bucket: &Bucket) -> u64 {
+    let (tolerable_faults, fragment_count) = match *bucket {
+        Bucket::Metadata(ref b) => (b.tolerable_faults, 0),
+        Bucket::Replicated(ref b) => (b.tolerable_faults, 0),
+        Bucket::Dispersed(ref b) => (
+            b.tolerable_faults,
+            fragment_count(b.data_fragment_count, b.tolerable_faults, &b.erasure_coding),
+        ),
+        Bucket::Hybrid(ref b) => (
+            b.raft_tolerable_faults(),
+            fragment_count(
+                b.data_fragment_count,
+                b.dispersed_tolerable_faults,
+                &b.erasure_coding,
+            ),
+        ),
+    };
+    let raft_cluster_size = u64::from(tolerable_faults) * 2 + 1;
+    std::cmp::max(raft_cluster_size, fragment_count)
+}
+
+fn fragment_count(data_fragment_count: u32, tolerable_faults: u32, ec: &ErasureCoding) -> u64 {
+    let local_parity_count = ec.scheme.local_parity_count(data_fragment_count);
+    u64::from(data_fragment_count) + u64::from(tolerable_faults) + u64::from(local_parity_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// 見つかった問題を`"<対象>: <フィールド>"`形式の文字列で返す。
+    ///
+    /// 警告の場合には先頭に`"warning: "`が付与される。
+    fn summary(problems: Vec<Problem>) -> Vec<String> {
+        let mut summary = problems
+            .into_iter()
+            .map(|p| {
+                let prefix = if p.is_error() { "" } else { "warning: " };
+                let field = p.field.as_deref().unwrap_or("-");
+                format!("{}{}: {}", prefix, p.target, field)
+            })
+            .collect::<Vec<_>>();
+        summary.sort();
+        summary
+    }
+
+    fn server(value: Value) -> Server {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn device(value: Value) -> Device {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn bucket(value: Value) -> Bucket {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn config(value: Value) -> ClusterConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn memory(id: &str, host: &str) -> Value {
+        json!({"memory": {
+            "id": id,
+            "server": "s1",
+            "capacity": 100,
+            "failure_domain": {"host": host}
+        }})
+    }
+
+    #[test]
+    fn validate_server_works() {
+        let s = server(json!({"id": "s1", "host": "127.0.0.1", "port": 14278}));
+        assert!(validate_server(&s).is_empty());
+
+        let s = server(json!({"id": "", "host": "127.0.0.1", "port": 0}));
+        assert_eq!(
+            summary(validate_server(&s)),
+            ["server : id", "server : port"]
+        );
+    }
+
+    #[test]
+    fn validate_device_works() {
+        let d = device(memory("m1", "h1"));
+        assert!(validate_device(&d).is_empty());
+
+        let d = device(json!({"memory": {"id": "m1", "server": "", "capacity": 0}}));
+        assert_eq!(
+            summary(validate_device(&d)),
+            ["device m1: capacity", "device m1: server"]
+        );
+
+        let d =
+            device(json!({"virtual": {"id": "v1", "children": [], "weight": {"relative": -1.0}}}));
+        assert_eq!(
+            summary(validate_device(&d)),
+            ["device v1: children", "device v1: weight"]
+        );
+
+        let d = device(json!({"virtual": {"id": "v1", "children": ["v1"]}}));
+        assert_eq!(summary(validate_device(&d)), ["device v1: children"]);
+
+        for capacity in &[
+            json!(1024),
+            json!("500GiB"),
+            json!("80%"),
+            json!("99% free"),
+        ] {
+            let d = device(json!({"file": {
+                "id": "f1",
+                "server": "s1",
+                "capacity": capacity,
+                "filepath": "/data/f1/lump"
+            }}));
+            assert!(validate_device(&d).is_empty(), "{}", capacity);
+        }
+
+        let mut d = device(json!({"file": {"id": "f1", "server": "s1", "filepath": "/"}}));
+        if let Device::File(ref mut f) = d {
+            f.capacity = CapacitySpec::PercentOfFree(150.0);
+        }
+        assert_eq!(
+            summary(validate_device(&d)),
+            ["device f1: capacity", "device f1: filepath"]
+        );
+    }
+
+    #[test]
+    fn validate_bucket_works() {
+        let b = bucket(json!({"dispersed": {
+            "id": "b1",
+            "device": "d",
+            "tolerable_faults": 1,
+            "data_fragment_count": 4,
+            "erasure_coding": {"scheme": {"locally_repairable": {"local_group_size": 2}}},
+            "lifecycle_rules": [{"expiration_days": 1}]
+        }}));
+        assert!(validate_bucket(&b).is_empty());
+
+        let b = bucket(json!({"dispersed": {
+            "id": "b1",
+            "device": "",
+            "segment_count": 70_000,
+            "tolerable_faults": 1,
+            "data_fragment_count": 0,
+            "erasure_coding": {
+                "scheme": {"locally_repairable": {"local_group_size": 1}},
+                "fragment_alignment": 1000
+            },
+            "lifecycle_rules": [{"prefix": "tmp/", "expiration_days": 0}]
+        }}));
+        assert_eq!(
+            summary(validate_bucket(&b)),
+            [
+                "bucket b1: data_fragment_count",
+                "bucket b1: device",
+                "bucket b1: erasure_coding.fragment_alignment",
+                "bucket b1: erasure_coding.scheme.local_group_size",
+                "bucket b1: lifecycle_rules[0].expiration_days",
+                "bucket b1: segment_count",
+            ]
+        );
+
+        let b = bucket(json!({"hybrid": {
+            "id": "b1",
+            "device": "d",
+            "size_threshold": 0,
+            "replicated_tolerable_faults": 200,
+            "dispersed_tolerable_faults": 1,
+            "data_fragment_count": 4
+        }}));
+        assert_eq!(
+            summary(validate_bucket(&b)),
+            ["bucket b1: -", "bucket b1: size_threshold"]
+        );
+    }
+
+    #[test]
+    fn validate_config_works() {
+        let c = config(json!({
+            "servers": [{"id": "s1", "host": "127.0.0.1", "port": 14278}],
+            "devices": [
+                {"virtual": {"id": "root", "children": ["m1", "m2", "m3"], "policy": "SCATTER_BY_HOST"}},
+                memory("m1", "h1"),
+                memory("m2", "h2"),
+                memory("m3", "h3")
+            ],
+            "buckets": [{"replicated": {"id": "b1", "device": "root", "tolerable_faults": 1}}]
+        }));
+        assert!(validate_config(&c).is_empty());
+    }
+
+    #[test]
+    fn validate_config_detects_inconsistencies() {
+        let c = config(json!({
+            "servers": [
+                {"id": "s1", "host": "127.0.0.1", "port": 14278},
+                {"id": "s1", "host": "127.0.0.2", "port": 14278}
+            ],
+            "devices": [
+                {"virtual": {"id": "root", "children": ["m1", "v1", "unknown"]}},
+                {"virtual": {"id": "v1", "children": ["v2", "m1"]}},
+                {"virtual": {"id": "v2", "children": ["v1"]}},
+                memory("m1", "h1"),
+                {"memory": {"id": "m2", "server": "s2", "capacity": 100}},
+                {"file": {"id": "f1", "server": "s1", "filepath": "/data/f/lump"}},
+                {"file": {"id": "f2", "server": "s1", "filepath": "/data/f/lump"}},
+                {"file": {"id": "f2", "server": "s1", "filepath": "/data/f2/lump"}}
+            ],
+            "buckets": [
+                {"replicated": {"id": "b1", "device": "root", "tolerable_faults": 0}},
+                {"replicated": {"id": "b1", "device": "f1", "tolerable_faults": 0}},
+                {"replicated": {"id": "b2", "device": "unknown", "tolerable_faults": 0}}
+            ]
+        }));
+        assert_eq!(
+            summary(validate_config(&c)),
+            [
+                // IDの重複
+                "bucket b1: id",
+                // 存在しないデバイス
+                "bucket b2: device",
+                // f1と同じパス
+                "device f2: filepath",
+                // IDの重複
+                "device f2: id",
+                // 複数の仮想デバイス（root, v1）に所属
+                "device m1: -",
+                // 存在しないサーバ
+                "device m2: server",
+                // 存在しない子デバイス
+                "device root: children",
+                // 複数の仮想デバイス（root, v2）に所属
+                "device v1: -",
+                // 循環（v1 -> v2 -> v1）
+                "device v1: children",
+                // IDの重複
+                "server s1: id",
+                // どの仮想デバイスにもバケツにも使われていない物理デバイス（後に定義された方）
+                "warning: device f2: -",
+                // どの仮想デバイスにもバケツにも使われていない物理デバイス
+                "warning: device m2: -",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_config_checks_failure_domains() {
+        let mut c = config(json!({
+            "servers": [{"id": "s1", "host": "127.0.0.1", "port": 14278}],
+            "devices": [
+                {"virtual": {"id": "root", "children": ["v1", "v2"], "policy": "SCATTER_BY_ZONE"}},
+                {"virtual": {"id": "v1", "children": ["m1"]}},
+                {"virtual": {"id": "v2", "children": ["m2", "m3"]}},
+                {"memory": {"id": "m1", "server": "s1", "capacity": 100, "failure_domain": {"zone": "z1"}}},
+                {"memory": {"id": "m2", "server": "s1", "capacity": 100, "failure_domain": {"zone": "z2"}}},
+                {"memory": {"id": "m3", "server": "s1", "capacity": 100, "failure_domain": {"zone": "z3"}}}
+            ],
+            "buckets": [{"replicated": {"id": "b1", "device": "root", "tolerable_faults": 1}}]
+        }));
+        assert!(validate_config(&c).is_empty());
+
+        // 割当不可な中間デバイス配下のゾーンは数えられない
+        if let Device::Virtual(ref mut v) = c.devices[2] {
+            v.state = crate::entity::device::DeviceState::Draining;
+        }
+        assert_eq!(summary(validate_config(&c)), ["bucket b1: device"]);
+
+        // ゾーンが指定されていない物理デバイス
+        c.devices[2] = device(json!({"virtual": {"id": "v2", "children": ["m2", "m3"]}}));
+        c.devices[5] = device(json!({"memory": {"id": "m3", "server": "s1", "capacity": 100}}));
+        assert_eq!(
+            summary(validate_config(&c)),
+            ["bucket b1: device", "device m3: failure_domain"]
+        );
+    }
+}
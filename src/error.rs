@@ -15,6 +15,11 @@ impl From<std::ffi::NulError> for Error {
         ErrorKind::Other.cause(f).into()
     }
 }
+impl From<serde_json::Error> for Error {
+    fn from(f: serde_json::Error) -> Self {
+        ErrorKind::InvalidInput.cause(f).into()
+    }
+}
 impl From<std::num::ParseIntError> for Error {
     fn from(f: std::num::ParseIntError) -> Self {
         ErrorKind::InvalidInput.cause(f).into()
@@ -31,5 +36,30 @@ pub enum ErrorKind {
     NotLeader,
     Unexpected(Option<ObjectVersion>),
     Other,
+    QuotaExceeded,
 }
 impl TrackableErrorKind for ErrorKind {}
+
+#[cfg(test)]
+mod tests {
+    use bytecodec::bincode_codec::BincodeEncoder;
+    use bytecodec::EncodeExt;
+
+    use super::*;
+
+    fn variant_index(kind: ErrorKind) -> Vec<u8> {
+        let bytes = BincodeEncoder::<ErrorKind>::default()
+            .encode_into_bytes(kind)
+            .unwrap();
+        bytes[..4].to_vec()
+    }
+
+    #[test]
+    fn variant_indices_are_stable() {
+        // 既存のバリアントの番号は変えてはいけない
+        assert_eq!(variant_index(ErrorKind::InvalidInput), [0, 0, 0, 0]);
+        assert_eq!(variant_index(ErrorKind::NotLeader), [3, 0, 0, 0]);
+        assert_eq!(variant_index(ErrorKind::Other), [5, 0, 0, 0]);
+        assert_eq!(variant_index(ErrorKind::QuotaExceeded), [6, 0, 0, 0]);
+    }
+}
@@ -3,9 +3,10 @@ use crate::entity::object::ObjectVersion;
 use crate::{ErrorKind, Result};
 
 /// 操作対象オブジェクトに期待するバージョンを表現するためのデータ構造.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum Expect {
     /// 任意のバージョンに対して適用可能.
+    #[default]
     Any,
 
     /// オブジェクトが既に存在しない場合にのみ適用可能.
@@ -35,8 +36,3 @@ impl Expect {
         Ok(())
     }
 }
-impl Default for Expect {
-    fn default() -> Self {
-        Expect::Any
-    }
-}
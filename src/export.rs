@@ -0,0 +1,210 @@
+//! 構成情報のエクスポートおよびインポート。
+//!
+//! クラスタ全体の構成情報（`ClusterConfig`）を、人が読み書きしやすいJSONないしTOML形式の文書として扱う。
+//!
+//! `to_string`で生成した文書を`from_str`で読み込むと、
+//! 各エンティティをIDの昇順に並べ替えた点を除いて元と等しい`ClusterConfig`が得られることが保証される。
+//! ただしTOMLでは`i64`の範囲を超える整数を表現できないため、
+//! そのような値（e.g., 極端に大きな容量）を含む構成をTOML形式で書き出そうとするとエラーとなる。
+use futures::Future;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use trackable::error::ErrorKindExt;
+
+use crate::apply;
+use crate::client::config::Client;
+use crate::entity::config::ClusterConfig;
+use crate::{Error, ErrorKind, Result};
+
+/// 文書の形式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// JSON形式。
+    Json,
+
+    /// TOML形式。
+    Toml,
+}
+impl Format {
+    /// ファイルの拡張子から形式を推測する。
+    ///
+    /// 拡張子が`.toml`の場合には`Format::Toml`を、それ以外の場合には`Format::Json`を返す。
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("toml") => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            _ => track_panic!(ErrorKind::InvalidInput, "Unknown format: {:?}", s),
+        }
+    }
+}
+
+/// 構成情報を指定形式の文書に変換する。
+///
+/// 差分を確認しやすくするために、各エンティティはIDの昇順に並べ替えられる。
+pub fn to_string(config: &ClusterConfig, format: Format) -> Result<String> {
+    let mut config = config.clone();
+    config.servers.sort_by(|a, b| a.id.cmp(&b.id));
+    config.devices.sort_by(|a, b| a.id().cmp(b.id()));
+    config.buckets.sort_by(|a, b| a.id().cmp(b.id()));
+    match format {
+        Format::Json => track!(serde_json::to_string_pretty(&config).map_err(Error::from)),
+        Format::Toml => track!(toml::to_string_pretty(&config)
+            .map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e)))),
+    }
+}
+
+/// 指定形式の文書から構成情報を読み込む。
+pub fn from_str(s: &str, format: Format) -> Result<ClusterConfig> {
+    match format {
+        Format::Json => track!(serde_json::from_str(s).map_err(Error::from)),
+        Format::Toml => {
+            track!(toml::from_str(s).map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))))
+        }
+    }
+}
+
+/// 構成情報をファイルに書き出す。
+///
+/// 形式はファイルの拡張子から決定される（`Format::from_path`参照）。
+pub fn save<P: AsRef<Path>>(path: P, config: &ClusterConfig) -> Result<()> {
+    let path = path.as_ref();
+    let s = track!(to_string(config, Format::from_path(path)))?;
+    track!(fs::write(path, s).map_err(Error::from), "path={:?}", path)?;
+    Ok(())
+}
+
+/// 構成情報をファイルから読み込む。
+///
+/// 形式はファイルの拡張子から決定される（`Format::from_path`参照）。
+pub fn load<P: AsRef<Path>>(path: P) -> Result<ClusterConfig> {
+    let path = path.as_ref();
+    let s = track!(
+        fs::read_to_string(path).map_err(Error::from),
+        "path={:?}",
+        path
+    )?;
+    track!(from_str(&s, Format::from_path(path)), "path={:?}", path)
+}
+
+/// 稼働中のクラスタの構成情報を指定形式の文書として取得する。
+pub fn export(client: &Client, format: Format) -> impl Future<Item = String, Error = Error> {
+    apply::fetch(client).and_then(move |config| track!(to_string(&config, format)))
+}
+
+/// 構成情報をクラスタに登録する。
+///
+/// `config`に含まれるエンティティのうち、未登録ないし内容が異なるもののみが登録される。
+/// `config`に含まれないエンティティは削除されない。
+pub fn import(client: &Client, config: ClusterConfig) -> impl Future<Item = (), Error = Error> {
+    let client = client.clone();
+    apply::fetch(&client)
+        .and_then(move |current| apply::plan(&config, &current, false).apply(&client))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::consistency::ReadConsistency;
+    use crate::entity::bucket::{Bucket, LifecycleRule};
+    use crate::entity::device::{CapacitySpec, Device};
+
+    fn config() -> ClusterConfig {
+        serde_json::from_value(json!({
+            "servers": [
+                {"id": "s1", "host": "127.0.0.1", "port": 14278, "failure_domain": {"zone": "z1"}}
+            ],
+            "devices": [
+                {"file": {"id": "f1", "server": "s1", "capacity": "500GiB", "filepath": "/data/f1/lump"}},
+                {"file": {"id": "f2", "server": "s1", "capacity": "80%", "filepath": "/data/f2/lump"}},
+                {"file": {"id": "f3", "server": "s1", "filepath": "/data/f3/lump"}},
+                {"file": {"id": "f4", "server": "s1", "capacity": "12.5% free", "filepath": "/data/f4/lump"}},
+                {"virtual": {"id": "root", "children": ["f1", "f2", "f3", "f4"], "policy": "SCATTER_BY_HOST"}}
+            ],
+            "buckets": [
+                {"dispersed": {
+                    "id": "b1",
+                    "device": "root",
+                    "tolerable_faults": 1,
+                    "data_fragment_count": 2,
+                    "defaults": {
+                        "multiplicity": {"inner_retry_count": 1, "number_of_ensured_saves": 2},
+                        "read_consistency": {"Subset": 2},
+                        "deadline": {"secs": 5, "nanos": 500_000_000}
+                    },
+                    "lifecycle_rules": [
+                        {"prefix": "tmp/", "expiration_days": 7},
+                        {"expiration_days": 365}
+                    ]
+                }},
+                {"replicated": {"id": "b2", "device": "f1", "tolerable_faults": 0}}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trip_works() {
+        let config = config();
+        let capacities = config
+            .devices
+            .iter()
+            .filter_map(|d| match *d {
+                Device::File(ref f) => Some(f.capacity),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            capacities,
+            [
+                CapacitySpec::Bytes(500 << 30),
+                CapacitySpec::PercentOfFilesystem(80.0),
+                CapacitySpec::default(),
+                CapacitySpec::PercentOfFree(12.5),
+            ]
+        );
+        let b1 = &config.buckets[0];
+        assert_eq!(
+            b1.defaults().read_consistency,
+            Some(ReadConsistency::Subset(2))
+        );
+        assert_eq!(b1.defaults().deadline, Some(Duration::from_millis(5500)));
+        assert_eq!(b1.lifecycle_rules()[0].prefix.0, "tmp/");
+
+        for &format in &[Format::Json, Format::Toml] {
+            let s = to_string(&config, format).unwrap();
+            let imported = from_str(&s, format).unwrap();
+            assert_eq!(imported, config, "{:?}:\n{}", format, s);
+        }
+    }
+
+    #[test]
+    fn entities_are_sorted_by_id() {
+        let mut config = config();
+        config.buckets.reverse();
+        config.devices.reverse();
+        let imported = from_str(&to_string(&config, Format::Toml).unwrap(), Format::Toml).unwrap();
+        assert_eq!(imported, self::config());
+        assert!(matches!(imported.buckets[1], Bucket::Replicated(_)));
+        assert_eq!(
+            imported.buckets[0].lifecycle_rules()[1],
+            LifecycleRule {
+                prefix: Default::default(),
+                expiration_days: 365,
+            }
+        );
+    }
+}
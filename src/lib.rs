@@ -8,6 +8,8 @@ extern crate fibers_rpc;
 extern crate futures;
 extern crate libc;
 extern crate serde;
+extern crate serde_json;
+extern crate toml;
 
 #[macro_use]
 extern crate serde_derive;
@@ -16,14 +18,18 @@ extern crate trackable;
 
 pub use crate::error::{Error, ErrorKind};
 
+pub mod apply;
+pub mod calculator;
 pub mod client;
 pub mod consistency;
 pub mod deadline;
 pub mod entity;
 pub mod expect;
+pub mod export;
 pub mod multiplicity;
 pub mod repair;
 pub mod schema;
+pub mod simulator;
 pub mod time;
 
 mod error;
@@ -2,13 +2,24 @@
 use bytecodec::bincode_codec::{BincodeDecoder, BincodeEncoder};
 use fibers_rpc::{Call, ProcedureId};
 use std::net::SocketAddr;
-
-use crate::entity::bucket::{Bucket, BucketId, BucketSummary};
-use crate::entity::device::{Device, DeviceId, DeviceSummary};
+use std::time::Duration;
+
+use crate::entity::bucket::{Bucket, BucketId, BucketSummary, Redundancy};
+use crate::entity::config::{ActorId, AuditEntry, ConfigChanges, ConfigEntityId, ConfigRevision};
+use crate::entity::device::{Device, DeviceId, DeviceState, DeviceSummary};
+use crate::entity::impact::{FailureScenario, ImpactReport};
+use crate::entity::job::{JobId, JobStatus};
+use crate::entity::label::LabelSelector;
+use crate::entity::legacy;
 use crate::entity::server::{Server, ServerId, ServerSummary};
+use crate::entity::validation::Problem;
+use crate::time::Seconds;
 use crate::Result;
 
 /// サーバ一覧取得RPC。
+///
+/// 応答は旧形式の要約となる。
+/// ラベル等の新しいフィールドを取得するには`ListServersV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct ListServersRpc;
 impl Call for ListServersRpc {
@@ -19,12 +30,51 @@ impl Call for ListServersRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Vec<legacy::ServerSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ一覧取得RPC（新形式）。
+///
+/// `ListServersRpc`と同様だが、応答は新形式の要約となる。
+#[derive(Debug)]
+pub struct ListServersV2Rpc;
+impl Call for ListServersV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0002_000a);
+    const NAME: &'static str = "frugalos.config.server.list.v2";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ServerSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ラベルによるサーバ一覧取得RPC。
+///
+/// 要求で指定された`LabelSelector`に合致するラベル群を持つサーバのみが返される。
+#[derive(Debug)]
+pub struct ListServersByLabelRpc;
+impl Call for ListServersByLabelRpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0009);
+    const NAME: &'static str = "frugalos.config.server.list_by_label";
+
+    type Req = LabelSelector;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Vec<ServerSummary>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// サーバ情報取得RPC。
+///
+/// 応答は旧形式のサーバとなる。
+/// 障害ドメイン等の新しいフィールドを取得するには`GetServerV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct GetServerRpc;
 impl Call for GetServerRpc {
@@ -35,28 +85,53 @@ impl Call for GetServerRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Server>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ情報取得RPC（新形式）。
+///
+/// `GetServerRpc`と同様だが、応答は新形式のサーバとなる。
+#[derive(Debug)]
+pub struct GetServerV2Rpc;
+impl Call for GetServerV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0002_000b);
+    const NAME: &'static str = "frugalos.config.server.get.v2";
+
+    type Req = ServerId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Server>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// サーバ登録RPC。
+///
+/// 要求・応答は旧形式のサーバとなり、監査記録（`AuditEntry`）の主体は`None`となる。
+/// 新しいフィールドや主体を指定するには`PutServerV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct PutServerRpc;
 impl Call for PutServerRpc {
     const ID: ProcedureId = ProcedureId(0x0002_0002);
     const NAME: &'static str = "frugalos.config.server.put";
 
-    type Req = Server;
+    type Req = legacy::Server;
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
-    type Res = Result<Server>;
+    type Res = Result<legacy::Server>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// サーバ削除RPC。
+///
+/// 応答は旧形式のサーバとなる。
+/// サーバ上にデバイスが存在する場合には、それらのデバイスを列挙した`ErrorKind::InvalidInput`エラーとなる。
+/// 削除を強行する場合や、監査記録の主体を指定する場合には`DeleteServerV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct DeleteServerRpc;
 impl Call for DeleteServerRpc {
@@ -67,12 +142,106 @@ impl Call for DeleteServerRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Server>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ登録RPC（主体指定付き）。
+///
+/// `PutServerRpc`と同様だが、要求・応答は新形式のサーバとなり、要求に監査記録の主体を含められる。
+#[derive(Debug)]
+pub struct PutServerV2Rpc;
+impl Call for PutServerV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0007);
+    const NAME: &'static str = "frugalos.config.server.put.v2";
+
+    type Req = PutRequest<Server>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Server>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ削除RPC（主体・強行指定付き）。
+///
+/// `DeleteServerRpc`と同様だが、応答は新形式のサーバとなり、要求に監査記録の主体を含められる。
+/// `DeleteRequest::force`が`true`の場合には依存するエンティティが存在しても削除を行う。
+#[derive(Debug)]
+pub struct DeleteServerV2Rpc;
+impl Call for DeleteServerV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0008);
+    const NAME: &'static str = "frugalos.config.server.delete.v2";
+
+    type Req = DeleteRequest<ServerId>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Server>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// サーバ撤去RPC。
+///
+/// サーバを撤去するジョブ（`JobKind::DecommissionServer`）を開始し、その初期状態を返す。
+/// ジョブの進捗は`GetJobStatusRpc`で確認できる。
+#[derive(Debug)]
+pub struct DecommissionServerRpc;
+impl Call for DecommissionServerRpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0006);
+    const NAME: &'static str = "frugalos.config.server.decommission";
+
+    type Req = DecommissionServerRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<JobStatus>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ上のデバイス一覧取得RPC。
+#[derive(Debug)]
+pub struct ListDevicesByServerRpc;
+impl Call for ListDevicesByServerRpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0005);
+    const NAME: &'static str = "frugalos.config.server.devices";
+
+    type Req = ServerId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<DeviceSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// サーバ登録内容の検証RPC。
+///
+/// `PutServerRpc`と同じ検証を行い、見つかった問題の一覧を返す。
+/// 構成情報は変更されない。
+#[derive(Debug)]
+pub struct ValidateServerRpc;
+impl Call for ValidateServerRpc {
+    const ID: ProcedureId = ProcedureId(0x0002_0004);
+    const NAME: &'static str = "frugalos.config.server.validate";
+
+    type Req = Server;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Problem>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// デバイス一覧取得RPC。
+///
+/// 応答は旧形式の要約となる。
+/// ラベル等の新しいフィールドを取得するには`ListDevicesV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct ListDevicesRpc;
 impl Call for ListDevicesRpc {
@@ -83,12 +252,51 @@ impl Call for ListDevicesRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Vec<legacy::DeviceSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス一覧取得RPC（新形式）。
+///
+/// `ListDevicesRpc`と同様だが、応答は新形式の要約となる。
+#[derive(Debug)]
+pub struct ListDevicesV2Rpc;
+impl Call for ListDevicesV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0003_000c);
+    const NAME: &'static str = "frugalos.config.device.list.v2";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<DeviceSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ラベルによるデバイス一覧取得RPC。
+///
+/// 要求で指定された`LabelSelector`に合致するラベル群を持つデバイスのみが返される。
+#[derive(Debug)]
+pub struct ListDevicesByLabelRpc;
+impl Call for ListDevicesByLabelRpc {
+    const ID: ProcedureId = ProcedureId(0x0003_000a);
+    const NAME: &'static str = "frugalos.config.device.list_by_label";
+
+    type Req = LabelSelector;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Vec<DeviceSummary>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// デバイス情報取得RPC。
+///
+/// 応答は旧形式のデバイスとなる。
+/// 稼働状態等の新しいフィールドを取得するには`GetDeviceV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct GetDeviceRpc;
 impl Call for GetDeviceRpc {
@@ -99,28 +307,54 @@ impl Call for GetDeviceRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Device>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス情報取得RPC（新形式）。
+///
+/// `GetDeviceRpc`と同様だが、応答は新形式のデバイスとなる。
+#[derive(Debug)]
+pub struct GetDeviceV2Rpc;
+impl Call for GetDeviceV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0003_000b);
+    const NAME: &'static str = "frugalos.config.device.get.v2";
+
+    type Req = DeviceId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Device>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// デバイス登録RPC。
+///
+/// 要求・応答は旧形式のデバイスとなり、監査記録（`AuditEntry`）の主体は`None`となる。
+/// 新しいフィールドや主体を指定するには`PutDeviceV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct PutDeviceRpc;
 impl Call for PutDeviceRpc {
     const ID: ProcedureId = ProcedureId(0x0003_0002);
     const NAME: &'static str = "frugalos.config.device.put";
 
-    type Req = Device;
+    type Req = legacy::Device;
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
-    type Res = Result<Device>;
+    type Res = Result<legacy::Device>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// デバイス削除RPC。
+///
+/// デバイスを（仮想デバイス経由で間接的に）使用しているバケツや、デバイスを子に持つ仮想デバイスが存在する場合には、
+/// それらを列挙した`ErrorKind::InvalidInput`エラーとなる。
+/// 応答は旧形式のデバイスとなる。
+/// 削除を強行する場合や、監査記録の主体を指定する場合には`DeleteDeviceV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct DeleteDeviceRpc;
 impl Call for DeleteDeviceRpc {
@@ -131,12 +365,135 @@ impl Call for DeleteDeviceRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Device>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス登録RPC（主体指定付き）。
+///
+/// `PutDeviceRpc`と同様だが、要求・応答は新形式のデバイスとなり、要求に監査記録の主体を含められる。
+#[derive(Debug)]
+pub struct PutDeviceV2Rpc;
+impl Call for PutDeviceV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0008);
+    const NAME: &'static str = "frugalos.config.device.put.v2";
+
+    type Req = PutRequest<Device>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Device>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス削除RPC（主体・強行指定付き）。
+///
+/// `DeleteDeviceRpc`と同様だが、応答は新形式のデバイスとなり、要求に監査記録の主体を含められる。
+/// `DeleteRequest::force`が`true`の場合には依存するエンティティが存在しても削除を行う。
+#[derive(Debug)]
+pub struct DeleteDeviceV2Rpc;
+impl Call for DeleteDeviceV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0009);
+    const NAME: &'static str = "frugalos.config.device.delete.v2";
+
+    type Req = DeleteRequest<DeviceId>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Device>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// デバイスの稼働状態変更RPC。
+///
+/// `DeviceState::can_transition_to`が`false`を返す遷移や、
+/// データの移行が完了していないデバイスの`DeviceState::Retired`への遷移は、
+/// `ErrorKind::InvalidInput`エラーとなる。
+#[derive(Debug)]
+pub struct SetDeviceStateRpc;
+impl Call for SetDeviceStateRpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0004);
+    const NAME: &'static str = "frugalos.config.device.set_state";
+
+    type Req = SetDeviceStateRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Device>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス交換RPC。
+///
+/// 物理デバイスを交換するジョブ（`JobKind::ReplaceDevice`）を開始し、その初期状態を返す。
+/// バケツの定義は変更されない。
+/// ジョブの進捗は`GetJobStatusRpc`で確認できる。
+///
+/// 旧デバイスないし新しいデバイスが仮想デバイスの場合や、新しいデバイスのIDが既に使用されている場合には、
+/// `ErrorKind::InvalidInput`エラーとなる。
+#[derive(Debug)]
+pub struct ReplaceDeviceRpc;
+impl Call for ReplaceDeviceRpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0007);
+    const NAME: &'static str = "frugalos.config.device.replace";
+
+    type Req = ReplaceDeviceRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<JobStatus>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイスを使用しているバケツ一覧取得RPC。
+///
+/// バケツのデバイスが指定デバイス自身の場合に加えて、
+/// 指定デバイスを配下に含む仮想デバイスの場合も対象となる。
+#[derive(Debug)]
+pub struct ListBucketsByDeviceRpc;
+impl Call for ListBucketsByDeviceRpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0006);
+    const NAME: &'static str = "frugalos.config.device.buckets";
+
+    type Req = DeviceId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<BucketSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// デバイス登録内容の検証RPC。
+///
+/// `PutDeviceRpc`と同じ検証（e.g., 子デバイスやサーバの存在確認、ファイルパスへの到達可能性）を行い、
+/// 見つかった問題の一覧を返す。
+/// 構成情報は変更されない。
+#[derive(Debug)]
+pub struct ValidateDeviceRpc;
+impl Call for ValidateDeviceRpc {
+    const ID: ProcedureId = ProcedureId(0x0003_0005);
+    const NAME: &'static str = "frugalos.config.device.validate";
+
+    type Req = Device;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Problem>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// バケツ一覧取得RPC。
+///
+/// 応答は旧形式の要約となる。
+/// 旧形式で表現できない`HybridBucket`は一覧に含まれない。
+/// ラベル等の新しいフィールドを取得するには`ListBucketsV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct ListBucketsRpc;
 impl Call for ListBucketsRpc {
@@ -147,12 +504,51 @@ impl Call for ListBucketsRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Vec<legacy::BucketSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ一覧取得RPC（新形式）。
+///
+/// `ListBucketsRpc`と同様だが、応答は新形式の要約となる。
+#[derive(Debug)]
+pub struct ListBucketsV2Rpc;
+impl Call for ListBucketsV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0004_000a);
+    const NAME: &'static str = "frugalos.config.bucket.list.v2";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<BucketSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ラベルによるバケツ一覧取得RPC。
+///
+/// 要求で指定された`LabelSelector`に合致するラベル群を持つバケツのみが返される。
+#[derive(Debug)]
+pub struct ListBucketsByLabelRpc;
+impl Call for ListBucketsByLabelRpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0009);
+    const NAME: &'static str = "frugalos.config.bucket.list_by_label";
+
+    type Req = LabelSelector;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Vec<BucketSummary>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// バケツ情報取得RPC。
+///
+/// 応答は旧形式のバケツとなる。
+/// 容量制限等の新しいフィールドを取得するには`GetBucketV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct GetBucketRpc;
 impl Call for GetBucketRpc {
@@ -163,28 +559,53 @@ impl Call for GetBucketRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Bucket>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ情報取得RPC（新形式）。
+///
+/// `GetBucketRpc`と同様だが、応答は新形式のバケツとなる。
+#[derive(Debug)]
+pub struct GetBucketV2Rpc;
+impl Call for GetBucketV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0004_000b);
+    const NAME: &'static str = "frugalos.config.bucket.get.v2";
+
+    type Req = BucketId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Bucket>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// バケツ登録RPC。
+///
+/// 要求・応答は旧形式のバケツとなり、監査記録（`AuditEntry`）の主体は`None`となる。
+/// 新しいフィールドや主体を指定するには`PutBucketV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct PutBucketRpc;
 impl Call for PutBucketRpc {
     const ID: ProcedureId = ProcedureId(0x0004_0002);
     const NAME: &'static str = "frugalos.config.bucket.put";
 
-    type Req = Bucket;
+    type Req = legacy::Bucket;
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
-    type Res = Result<Bucket>;
+    type Res = Result<legacy::Bucket>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
 /// バケツ削除RPC。
+///
+/// 応答は旧形式のバケツとなる。
+/// 監査記録（`AuditEntry`）の主体は`None`となる。
+/// 主体を指定するには`DeleteBucketV2Rpc`を使用する。
 #[derive(Debug)]
 pub struct DeleteBucketRpc;
 impl Call for DeleteBucketRpc {
@@ -195,11 +616,115 @@ impl Call for DeleteBucketRpc {
     type ReqDecoder = BincodeDecoder<Self::Req>;
     type ReqEncoder = BincodeEncoder<Self::Req>;
 
+    type Res = Result<Option<legacy::Bucket>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ登録RPC（主体指定付き）。
+///
+/// `PutBucketRpc`と同様だが、要求・応答は新形式のバケツとなり、要求に監査記録の主体を含められる。
+#[derive(Debug)]
+pub struct PutBucketV2Rpc;
+impl Call for PutBucketV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0007);
+    const NAME: &'static str = "frugalos.config.bucket.put.v2";
+
+    type Req = PutRequest<Bucket>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Bucket>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ削除RPC（主体指定付き）。
+///
+/// `DeleteBucketRpc`と同様だが、応答は新形式のバケツとなり、要求に監査記録の主体を含められる。
+#[derive(Debug)]
+pub struct DeleteBucketV2Rpc;
+impl Call for DeleteBucketV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0008);
+    const NAME: &'static str = "frugalos.config.bucket.delete.v2";
+
+    type Req = DeleteRequest<BucketId>;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
     type Res = Result<Option<Bucket>>;
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// バケツ登録内容の検証RPC。
+///
+/// `PutBucketRpc`と同じ検証（e.g., デバイスの存在確認、デバイスグループを構成可能かどうか）を行い、
+/// 見つかった問題の一覧を返す。
+/// 構成情報は変更されない。
+#[derive(Debug)]
+pub struct ValidateBucketRpc;
+impl Call for ValidateBucketRpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0004);
+    const NAME: &'static str = "frugalos.config.bucket.validate";
+
+    type Req = Bucket;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<Problem>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツのセグメント数変更RPC。
+///
+/// バケツのセグメント数を変更するジョブ（`JobKind::ReshardBucket`）を開始し、その初期状態を返す。
+/// ジョブの実行中もバケツへの読み書きは継続して行える。
+/// ジョブの進捗は`GetJobStatusRpc`で確認できる。
+///
+/// セグメント数に`0`や現在と同じ値が指定された場合や、
+/// 対象バケツで他のジョブが実行中の場合には`ErrorKind::InvalidInput`エラーとなる。
+#[derive(Debug)]
+pub struct ReshardBucketRpc;
+impl Call for ReshardBucketRpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0005);
+    const NAME: &'static str = "frugalos.config.bucket.reshard";
+
+    type Req = ReshardBucketRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<JobStatus>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツの冗長化方式変更RPC。
+///
+/// バケツの冗長化方式を変更するジョブ（`JobKind::ChangeBucketRedundancy`）を開始し、その初期状態を返す。
+/// `ReplicatedBucket`・`DispersedBucket`・`HybridBucket`の間での種類の変更も可能である（`Bucket::set_redundancy`参照）。
+/// ただし`HybridBucket`への変更は、対象が既に`HybridBucket`である場合にのみ可能である。
+/// ジョブの実行中もバケツへの読み書きは継続して行える。
+/// ジョブの進捗は`GetJobStatusRpc`で確認できる。
+///
+/// 対象が`MetadataBucket`の場合や、`HybridBucket`以外のバケツに`Redundancy::Hybrid`が指定された場合、変更後の方式が現在と同じ場合、
+/// 対象バケツで他のジョブが実行中の場合には`ErrorKind::InvalidInput`エラーとなる。
+#[derive(Debug)]
+pub struct ChangeBucketRedundancyRpc;
+impl Call for ChangeBucketRedundancyRpc {
+    const ID: ProcedureId = ProcedureId(0x0004_0006);
+    const NAME: &'static str = "frugalos.config.bucket.change_redundancy";
+
+    type Req = ChangeBucketRedundancyRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<JobStatus>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// Raftのリーダノード取得RPC。
 // NOTE: リーダ選出中の場合にはserver側でwaitする
 #[derive(Debug)]
@@ -216,3 +741,243 @@ impl Call for GetLeaderRpc {
     type ResDecoder = BincodeDecoder<Self::Res>;
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
+
+/// 構成情報の現在のリビジョン取得RPC。
+#[derive(Debug)]
+pub struct GetConfigRevisionRpc;
+impl Call for GetConfigRevisionRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0000);
+    const NAME: &'static str = "frugalos.config.revision.get";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ConfigRevision>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 構成変更の監視RPC。
+///
+/// 構成情報のリビジョンが`WatchConfigRequest::revision`を超えるか、
+/// `WatchConfigRequest::timeout`が経過するまで、server側で応答を保留する。
+#[derive(Debug)]
+pub struct WatchConfigRpc;
+impl Call for WatchConfigRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0001);
+    const NAME: &'static str = "frugalos.config.watch";
+
+    type Req = WatchConfigRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ConfigChanges>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 構成変更の監視要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfigRequest {
+    /// 呼び出し側が既に把握しているリビジョン。
+    ///
+    /// これより後の変更が応答に含まれる。
+    pub revision: ConfigRevision,
+
+    /// 変更が無い場合に応答を保留する最大時間。
+    pub timeout: Duration,
+}
+
+/// 構成変更の履歴取得RPC。
+#[derive(Debug)]
+pub struct ListConfigHistoryRpc;
+impl Call for ListConfigHistoryRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0002);
+    const NAME: &'static str = "frugalos.config.history.list";
+
+    type Req = ConfigHistoryRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<AuditEntry>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// 障害影響分析RPC。
+///
+/// 要求で指定されたサーバ・デバイス群が停止した場合に影響を受けるバケツおよびセグメントを、
+/// 実際のセグメント割当に基づいて返す。
+/// 構成情報は変更されない。
+#[derive(Debug)]
+pub struct AnalyzeFailureImpactRpc;
+impl Call for AnalyzeFailureImpactRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0003);
+    const NAME: &'static str = "frugalos.config.impact.analyze";
+
+    type Req = FailureScenario;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<ImpactReport>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ジョブの状態取得RPC。
+#[derive(Debug)]
+pub struct GetJobStatusRpc;
+impl Call for GetJobStatusRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0004);
+    const NAME: &'static str = "frugalos.config.job.get";
+
+    type Req = JobId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<JobStatus>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// ジョブ一覧取得RPC。
+///
+/// 終了済みのジョブも含めて、作成時刻の昇順で返す。
+#[derive(Debug)]
+pub struct ListJobsRpc;
+impl Call for ListJobsRpc {
+    const ID: ProcedureId = ProcedureId(0x0006_0005);
+    const NAME: &'static str = "frugalos.config.job.list";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<JobStatus>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// エンティティの登録要求。
+///
+/// `PutServerV2Rpc`等で使用される。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutRequest<T> {
+    /// 登録するエンティティ。
+    pub entity: T,
+
+    /// 変更を行う主体。
+    ///
+    /// 監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// エンティティの削除要求。
+///
+/// `DeleteServerV2Rpc`等で使用される。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteRequest<T> {
+    /// 削除するエンティティのID。
+    pub id: T,
+
+    /// 依存するエンティティが存在する場合でも削除を強行するかどうか。
+    ///
+    /// バケツの削除では無視される。
+    #[serde(default)]
+    pub force: bool,
+
+    /// 変更を行う主体。
+    ///
+    /// 監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// デバイスの稼働状態変更要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetDeviceStateRequest {
+    /// 対象デバイスのID。
+    pub device: DeviceId,
+
+    /// 遷移先の状態。
+    pub state: DeviceState,
+
+    /// 変更を行う主体。
+    ///
+    /// 監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// 構成変更の履歴取得要求。
+///
+/// 全ての条件を満たす記録が古いものから順に返される。
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ConfigHistoryRequest {
+    /// 対象とするエンティティ。
+    ///
+    /// `None`の場合には全てのエンティティが対象となる。
+    pub target: Option<ConfigEntityId>,
+
+    /// この時刻（UNIXエポックからの経過秒数）以降の記録のみを対象とする。
+    pub since: Option<Seconds>,
+
+    /// この時刻（UNIXエポックからの経過秒数）より前の記録のみを対象とする。
+    pub until: Option<Seconds>,
+}
+
+/// サーバ撤去要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecommissionServerRequest {
+    /// 撤去するサーバのID。
+    pub server: ServerId,
+
+    /// 変更を行う主体。
+    ///
+    /// ジョブおよび監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// デバイス交換要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceDeviceRequest {
+    /// 交換される旧デバイスのID。
+    pub old: DeviceId,
+
+    /// 交換後の新しいデバイス。
+    pub new: Device,
+
+    /// 変更を行う主体。
+    ///
+    /// ジョブおよび監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// バケツのセグメント数変更要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReshardBucketRequest {
+    /// 対象バケツのID。
+    pub bucket: BucketId,
+
+    /// 変更後のセグメント数。
+    pub segment_count: u16,
+
+    /// 変更を行う主体。
+    ///
+    /// ジョブおよび監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
+
+/// バケツの冗長化方式変更要求。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBucketRedundancyRequest {
+    /// 対象バケツのID。
+    pub bucket: BucketId,
+
+    /// 変更後の冗長化方式。
+    pub redundancy: Redundancy,
+
+    /// 変更を行う主体。
+    ///
+    /// ジョブおよび監査記録（`AuditEntry`）に残される。
+    pub actor: Option<ActorId>,
+}
@@ -6,8 +6,8 @@ use std::ops::Range;
 use std::time::Duration;
 
 use crate::consistency::ReadConsistency;
-use crate::entity::bucket::BucketId;
-use crate::entity::device::DeviceId;
+use crate::entity::bucket::{BucketId, BucketUsage, SegmentUsage};
+use crate::entity::device::{DeviceId, DeviceUsage};
 use crate::entity::object::{
     DeleteObjectsByPrefixSummary, FragmentsSummary, ObjectId, ObjectPrefix, ObjectSummary,
     ObjectVersion,
@@ -51,6 +51,9 @@ impl Call for HeadObjectRpc {
 }
 
 /// オブジェクト保存RPC。
+///
+/// 保存によってバケツの使用量が`BucketQuota`の上限を超える場合には、
+/// `ErrorKind::QuotaExceeded`エラーとなる。
 #[derive(Debug)]
 pub struct PutObjectRpc;
 impl Call for PutObjectRpc {
@@ -229,6 +232,120 @@ impl Call for CountFragmentsRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オブジェクト取得RPC（バケツの既定値を適用する版）。
+///
+/// `GetObjectRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct GetObjectV2Rpc;
+impl Call for GetObjectV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0010);
+    const NAME: &'static str = "frugalos.object.get.v2";
+
+    type Req = ObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    // FIXME: データが巨大になる可能性があるのでbincodeはやめる
+    type Res = Result<Option<(ObjectVersion, Vec<u8>)>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト存在確認RPC（バケツの既定値を適用する版）。
+///
+/// `HeadObjectRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct HeadObjectV2Rpc;
+impl Call for HeadObjectV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0011);
+    const NAME: &'static str = "frugalos.object.head.v2";
+
+    type Req = HeadObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectVersion>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト保存RPC（バケツの既定値を適用する版）。
+///
+/// `PutObjectRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct PutObjectV2Rpc;
+impl Call for PutObjectV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0012);
+    const NAME: &'static str = "frugalos.object.put.v2";
+
+    // FIXME: データが巨大になる可能性があるのでbincodeはやめる
+    type Req = PutObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(ObjectVersion, bool)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト削除RPC（バケツの既定値を適用する版）。
+///
+/// `DeleteObjectRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct DeleteObjectV2Rpc;
+impl Call for DeleteObjectV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0013);
+    const NAME: &'static str = "frugalos.object.delete.v2";
+
+    type Req = ObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<ObjectVersion>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// オブジェクト一覧取得RPC（バケツの既定値を適用する版）。
+///
+/// `ListObjectsRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct ListObjectsV2Rpc;
+impl Call for ListObjectsV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0014);
+    const NAME: &'static str = "frugalos.object.list.v2";
+
+    type Req = ListObjectsRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<ObjectSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+
+    fn enable_async_response(_: &Self::Res) -> bool {
+        true
+    }
+}
+
+/// フラグメントカウントRPC（バケツの既定値を適用する版）。
+///
+/// `CountFragmentsRpc`と同様だが、要求で省略された設定には対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug)]
+pub struct CountFragmentsV2Rpc;
+impl Call for CountFragmentsV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0009_0015);
+    const NAME: &'static str = "frugalos.object.count_fragments.v2";
+
+    type Req = ObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Option<FragmentsSummary>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト単位のRPC要求。
 #[allow(missing_docs)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -314,6 +431,80 @@ pub struct ListObjectsRequest {
     pub consistency: ReadConsistency,
 }
 
+/// オブジェクト単位の要求の設定のうち、省略可能なもの。
+///
+/// `None`の項目には、対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectOptions {
+    /// 要求のデッドライン（`BucketDefaults::deadline`）。
+    pub deadline: Option<Duration>,
+
+    /// 読み込み時の一貫性の保証レベル（`BucketDefaults::read_consistency`）。
+    ///
+    /// 読み込みを伴わない要求では無視される。
+    pub consistency: Option<ReadConsistency>,
+}
+
+/// オブジェクト保存要求の設定のうち、省略可能なもの。
+///
+/// 特に記載のない`None`の項目には、対象バケツの`BucketDefaults`の値が使用される。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PutObjectOptions {
+    /// 要求のデッドライン（`BucketDefaults::deadline`）。
+    pub deadline: Option<Duration>,
+
+    /// 多重度の設定（`BucketDefaults::multiplicity`）。
+    pub multiplicity_config: Option<MultiplicityConfig>,
+
+    /// 保存からオブジェクトが削除されるまでの期間。
+    ///
+    /// `None`の場合にはバケツの`LifecycleRule`群に従う。
+    pub ttl: Option<Duration>,
+}
+
+/// オブジェクト単位のRPC要求（`GetObjectV2Rpc`等で使用される）。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectRequestV2 {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub expect: Expect,
+    pub options: ObjectOptions,
+}
+
+/// オブジェクト単位の存在確認 RPC 要求（`HeadObjectV2Rpc`で使用される）。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadObjectRequestV2 {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub expect: Expect,
+    pub options: ObjectOptions,
+    /// ストレージ側にも問い合わせるかどうか
+    pub check_storage: bool,
+}
+
+/// オブジェクト保存要求（`PutObjectV2Rpc`で使用される）。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PutObjectRequestV2 {
+    pub bucket_id: BucketId,
+    pub object_id: ObjectId,
+    pub content: Vec<u8>,
+    pub expect: Expect,
+    pub options: PutObjectOptions,
+}
+
+/// オブジェクト一覧要求（`ListObjectsV2Rpc`で使用される）。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListObjectsRequestV2 {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+    /// `None`の場合にはバケツの`BucketDefaults::read_consistency`が使用される
+    pub consistency: Option<ReadConsistency>,
+}
+
 /// セグメント単位でのRPC要求。
 #[allow(missing_docs)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -383,6 +574,93 @@ impl Call for SetRepairConfigRpc {
     type ResDecoder = BincodeDecoder<Self::Res>;
 }
 
+/// デバイス使用状況取得RPC。
+///
+/// 仮想デバイスが指定された場合には、配下の物理デバイス群の使用状況を合算した値を返す。
+#[derive(Debug)]
+pub struct GetDeviceUsageRpc;
+impl Call for GetDeviceUsageRpc {
+    const ID: ProcedureId = ProcedureId(0x000b_0000);
+    const NAME: &'static str = "frugalos.device.usage.get";
+
+    type Req = DeviceId;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<DeviceUsage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// クラスタ内の全デバイスの使用状況取得RPC。
+///
+/// 仮想デバイスの使用状況は、配下の物理デバイス群の使用状況を合算した値となる。
+#[derive(Debug)]
+pub struct ListDeviceUsagesRpc;
+impl Call for ListDeviceUsagesRpc {
+    const ID: ProcedureId = ProcedureId(0x000b_0001);
+    const NAME: &'static str = "frugalos.device.usage.list";
+
+    type Req = ();
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<Vec<DeviceUsage>>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ使用量取得RPC。
+///
+/// 各セグメントの使用量は、要求の`consistency`に従って各セグメントのRaftクラスタから取得される。
+#[derive(Debug)]
+pub struct GetBucketUsageRpc;
+impl Call for GetBucketUsageRpc {
+    const ID: ProcedureId = ProcedureId(0x000c_0000);
+    const NAME: &'static str = "frugalos.bucket.usage.get";
+
+    type Req = BucketUsageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<BucketUsage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// セグメント使用量取得RPC。
+#[derive(Debug)]
+pub struct GetSegmentUsageRpc;
+impl Call for GetSegmentUsageRpc {
+    const ID: ProcedureId = ProcedureId(0x000c_0001);
+    const NAME: &'static str = "frugalos.bucket.usage.segment.get";
+
+    type Req = SegmentUsageRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<SegmentUsage>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
+/// バケツ使用量の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BucketUsageRequest {
+    pub bucket_id: BucketId,
+    pub consistency: ReadConsistency,
+}
+
+/// セグメント使用量の要求。
+#[allow(missing_docs)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SegmentUsageRequest {
+    pub bucket_id: BucketId,
+    pub segment: u16,
+    pub consistency: ReadConsistency,
+}
+
 /// バケツのデータ削除要求
 /// バケツ削除処理でデータ削除処理が失敗した場合に実施を想定
 pub struct TruncateBucketRpc;
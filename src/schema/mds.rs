@@ -10,6 +10,7 @@ use crate::entity::object::{
     DeleteObjectsByPrefixSummary, Metadata, ObjectId, ObjectPrefix, ObjectSummary, ObjectVersion,
 };
 use crate::expect::Expect;
+use crate::time::Seconds;
 use crate::Result;
 
 /// Raftのリーダ取得RPC。
@@ -108,6 +109,24 @@ impl Call for PutObjectRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// オブジェクト保存RPC（有効期限を指定可能な版）。
+///
+/// `PutObjectRpc`と同様だが、要求にオブジェクトの有効期限を含めることができる。
+#[derive(Debug)]
+pub struct PutObjectV2Rpc;
+impl Call for PutObjectV2Rpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000c);
+    const NAME: &'static str = "frugalos.mds.object.put.v2";
+
+    type Req = PutObjectRequestV2;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(ObjectVersion, Option<ObjectVersion>)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// オブジェクト削除RPC。
 #[derive(Debug)]
 pub struct DeleteObjectRpc;
@@ -188,6 +207,24 @@ impl Call for GetObjectCountRpc {
     type ResEncoder = BincodeEncoder<Self::Res>;
 }
 
+/// 格納済みオブジェクトの使用量取得RPC。
+///
+/// `(オブジェクト数, オブジェクトの内容の合計サイズ（バイト単位）)`を返す。
+#[derive(Debug)]
+pub struct GetObjectUsageRpc;
+impl Call for GetObjectUsageRpc {
+    const ID: ProcedureId = ProcedureId(0x0008_000b);
+    const NAME: &'static str = "frugalos.mds.object.usage";
+
+    type Req = ObjectCountRequest;
+    type ReqDecoder = BincodeDecoder<Self::Req>;
+    type ReqEncoder = BincodeEncoder<Self::Req>;
+
+    type Res = Result<(u64, u64)>;
+    type ResDecoder = BincodeDecoder<Self::Res>;
+    type ResEncoder = BincodeEncoder<Self::Res>;
+}
+
 /// 接頭辞削除RPC。
 #[derive(Debug)]
 pub struct DeleteObjectsByPrefixRpc;
@@ -284,3 +321,18 @@ pub struct PutObjectRequest {
     pub expect: Expect,
     pub put_content_timeout: Duration,
 }
+
+/// オブジェクト保存要求（`PutObjectV2Rpc`で使用される）。
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutObjectRequestV2 {
+    pub node_id: LocalNodeId,
+    pub object_id: ObjectId,
+    pub metadata: Vec<u8>,
+    pub expect: Expect,
+    pub put_content_timeout: Duration,
+    /// オブジェクトの有効期限（UNIXエポックからの経過秒数）
+    ///
+    /// 期限を過ぎたオブジェクトは、バックグラウンド処理によって削除される
+    pub expires_at: Option<Seconds>,
+}
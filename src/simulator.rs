@@ -0,0 +1,600 @@
+//! セグメント割当のシミュレータ。
+//!
+//! デバイスの木構造とバケツから、各セグメントのノード群がどの物理デバイスに割り当てられるかを、
+//! クラスタに登録することなく計算する。
+//!
+//! 割当はセグメント番号の昇順に、各セグメント内ではノード番号の昇順に一つずつ行われる。
+//! 一つのノードの割当は、バケツのデバイスから木構造を下りながら、
+//! 各仮想デバイスの`SegmentAllocationPolicy`に従って子デバイスを一つ選ぶことを物理デバイスに到達するまで繰り返す。
+//! 候補が複数ある場合には、`(割当済みノード数 + 1) / 重み`が最小の子デバイスが選ばれ、
+//! それでも決まらない場合にはIDが最小のものが選ばれる。
+//! 稼働状態が割当不可（`DeviceState::is_allocatable`参照）なデバイスや、重みが0のデバイスは、
+//! その配下のデバイスごと候補から除外される。
+//!
+//! `SegmentAllocationPolicy::ScatterByZone`等の障害ドメインを考慮する仮想デバイスでは、
+//! 子デバイスを経由せずに、配下の物理デバイス群のうち同じセグメント内で未使用の障害ドメインに属するものから直接選ばれる。
+//!
+//! なおこれは`frugalos`サーバの割当処理を単純化して再現した近似であり、
+//! 割当の順序や同点時の選択等が一致することは保証されない。
+//! 実際の割当結果の確認ではなく、構成の変更が割当の偏りに与える影響の見積もりに使用されることを想定している。
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::entity::bucket::{Bucket, BucketId};
+use crate::entity::config::ClusterConfig;
+use crate::entity::device::{
+    CapacitySpec, Device, DeviceId, SegmentAllocationPolicy, VirtualDevice,
+};
+use crate::entity::device_graph::DeviceGraph;
+use crate::entity::failure_domain::{FailureDomain, FailureDomainLevel};
+use crate::{ErrorKind, Result};
+
+/// セグメント割当のシミュレータ。
+#[derive(Debug, Clone)]
+pub struct Simulator<'a> {
+    config: &'a ClusterConfig,
+    graph: DeviceGraph<'a>,
+    cycle: Option<Vec<DeviceId>>,
+    capacities: BTreeMap<DeviceId, u64>,
+}
+impl<'a> Simulator<'a> {
+    /// 新しい`Simulator`インスタンスを生成する。
+    pub fn new(config: &'a ClusterConfig) -> Self {
+        let graph = DeviceGraph::new(&config.devices);
+        let cycle = graph
+            .cycles()
+            .into_iter()
+            .next()
+            .map(|c| c.into_iter().cloned().collect());
+        Simulator {
+            config,
+            graph,
+            cycle,
+            capacities: BTreeMap::new(),
+        }
+    }
+
+    /// 物理デバイスの容量（バイト単位）を明示的に指定する。
+    ///
+    /// 容量がファイルシステムに対する割合で指定されているファイルデバイスは、
+    /// この方法で容量を与えない限りシミュレーションに使用できない。
+    pub fn set_capacity(&mut self, device: DeviceId, capacity: u64) {
+        self.capacities.insert(device, capacity);
+    }
+
+    /// デバイスの容量（バイト単位）を返す。
+    ///
+    /// 仮想デバイスの場合には、子デバイス群の容量の合計となる。
+    ///
+    /// デバイスの木構造に循環が含まれる場合には`ErrorKind::InvalidInput`が返される。
+    pub fn capacity(&self, device: &DeviceId) -> Result<u64> {
+        track!(self.check_acyclic())?;
+        if let Some(&capacity) = self.capacities.get(device) {
+            return Ok(capacity);
+        }
+        match *track!(self.get(device))? {
+            Device::Virtual(ref d) => {
+                let mut total = 0;
+                for child in &d.children {
+                    total += track!(self.capacity(child))?;
+                }
+                Ok(total)
+            }
+            Device::Memory(ref d) => Ok(d.capacity),
+            Device::File(ref d) => match d.capacity {
+                CapacitySpec::Bytes(capacity) => Ok(capacity),
+                ref spec => track_panic!(
+                    ErrorKind::InvalidInput,
+                    "The capacity of device {:?} depends on its filesystem ({}); \
+                     specify it by `Simulator::set_capacity`",
+                    d.id,
+                    spec
+                ),
+            },
+        }
+    }
+
+    /// 割当の際に使用されるデバイスの重みを返す。
+    ///
+    /// 割当不可な稼働状態のデバイスの重みは0となる。
+    /// 仮想デバイスの`Weight::Auto`は、子デバイス群の重みの合計を意味する。
+    ///
+    /// デバイスの木構造に循環が含まれる場合には`ErrorKind::InvalidInput`が返される。
+    pub fn weight(&self, device: &DeviceId) -> Result<u64> {
+        track!(self.check_acyclic())?;
+        let d = track!(self.get(device))?;
+        if !d.state().is_allocatable() {
+            return Ok(0);
+        }
+        match *d {
+            Device::Virtual(ref v) => {
+                let mut total = 0;
+                for child in &v.children {
+                    total += track!(self.weight(child))?;
+                }
+                Ok(v.weight.calculate(total))
+            }
+            Device::Memory(ref m) => Ok(m.weight.calculate(track!(self.capacity(&m.id))?)),
+            Device::File(ref f) => Ok(f.weight.calculate(track!(self.capacity(&f.id))?)),
+        }
+    }
+
+    /// バケツのセグメント群の割当をシミュレートする。
+    ///
+    /// バケツのセグメント数が0の場合には、`Bucket::fix_segment_count`と同様に自動で決定される。
+    ///
+    /// # Errors
+    ///
+    /// デバイスの木構造が不正な場合（循環を含む場合等）や、`SegmentAllocationPolicy::Scatter`等の制約を満たす割当が存在しない場合には、
+    /// `ErrorKind::InvalidInput`が返される。
+    pub fn simulate(&self, bucket: &Bucket) -> Result<Placement> {
+        track!(self.check_acyclic())?;
+        let allocatable = track!(self.allocatable_leaf_paths(bucket.device()))?.len();
+        let mut bucket = bucket.clone();
+        bucket.fix_segment_count(allocatable);
+
+        let mut loads = BTreeMap::new();
+        let mut segments = Vec::with_capacity(usize::from(bucket.segment_count()));
+        for _ in 0..bucket.segment_count() {
+            let mut segment = SegmentState::default();
+            let mut nodes = Vec::with_capacity(usize::from(bucket.device_group_size()));
+            for _ in 0..bucket.device_group_size() {
+                let mut path = Vec::new();
+                track!(self.select(bucket.device(), &loads, &mut segment, &mut path))?;
+                for id in &path {
+                    *loads.entry((*id).clone()).or_insert(0) += 1;
+                    *segment.used.entry((*id).clone()).or_insert(0) += 1;
+                }
+                nodes.push(path.last().cloned().expect("Never fails").clone());
+            }
+            segments.push(nodes);
+        }
+        Ok(Placement {
+            bucket: bucket.id().clone(),
+            segments,
+            loads,
+        })
+    }
+
+    fn check_acyclic(&self) -> Result<()> {
+        if let Some(ref cycle) = self.cycle {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                "Device graph contains a cycle: {:?}",
+                cycle
+            );
+        }
+        Ok(())
+    }
+
+    fn get(&self, device: &DeviceId) -> Result<&'a Device> {
+        let d = track_assert_some!(
+            self.graph.get(device),
+            ErrorKind::InvalidInput,
+            "Unknown device: {:?}",
+            device
+        );
+        Ok(d)
+    }
+
+    fn select(
+        &self,
+        device: &'a DeviceId,
+        loads: &BTreeMap<DeviceId, usize>,
+        segment: &mut SegmentState,
+        path: &mut Vec<&'a DeviceId>,
+    ) -> Result<()> {
+        let v = match *track!(self.get(device))? {
+            Device::Virtual(ref v) => v,
+            ref d => {
+                track_assert!(
+                    d.state().is_allocatable() && track!(self.weight(d.id()))? > 0,
+                    ErrorKind::InvalidInput,
+                    "No allocatable device: {:?}",
+                    d.id()
+                );
+                path.push(d.id());
+                return Ok(());
+            }
+        };
+        path.push(&v.id);
+
+        let mut children = Vec::new();
+        for child in &v.children {
+            let weight = track!(self.weight(child))?;
+            if weight > 0 {
+                children.push((child, weight));
+            }
+        }
+        track_assert!(
+            !children.is_empty(),
+            ErrorKind::InvalidInput,
+            "No allocatable children: device={:?}",
+            v.id
+        );
+        let used = |c: &(&DeviceId, u64)| segment.used.get(c.0).cloned().unwrap_or(0);
+        let unused = children
+            .iter()
+            .filter(|c| used(c) == 0)
+            .cloned()
+            .collect::<Vec<_>>();
+        let chosen = match v.policy {
+            SegmentAllocationPolicy::Neutral => least_loaded(children, loads, |c| *c),
+            SegmentAllocationPolicy::ScatterIfPossible => {
+                if unused.is_empty() {
+                    least_loaded(children, loads, |c| *c)
+                } else {
+                    least_loaded(unused, loads, |c| *c)
+                }
+            }
+            SegmentAllocationPolicy::Scatter => {
+                let chosen = least_loaded(unused, loads, |c| *c);
+                track_assert!(
+                    chosen.is_some(),
+                    ErrorKind::InvalidInput,
+                    "Not enough children under device {:?} to scatter the segment",
+                    v.id
+                );
+                chosen
+            }
+            SegmentAllocationPolicy::Gather => {
+                if let Some(c) = children.iter().find(|c| used(c) > 0) {
+                    Some(*c)
+                } else {
+                    least_loaded(children, loads, |c| *c)
+                }
+            }
+            SegmentAllocationPolicy::AsEvenAsPossible => {
+                let min = children.iter().map(&used).min().expect("Never fails");
+                let even = children
+                    .iter()
+                    .filter(|c| used(c) == min)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                least_loaded(even, loads, |c| *c)
+            }
+            SegmentAllocationPolicy::ScatterByZone => {
+                let level = FailureDomainLevel::Zone;
+                return track!(self.select_by_failure_domain(v, level, loads, segment, path));
+            }
+            SegmentAllocationPolicy::ScatterByRack => {
+                let level = FailureDomainLevel::Rack;
+                return track!(self.select_by_failure_domain(v, level, loads, segment, path));
+            }
+            SegmentAllocationPolicy::ScatterByHost => {
+                let level = FailureDomainLevel::Host;
+                return track!(self.select_by_failure_domain(v, level, loads, segment, path));
+            }
+        };
+        let (child, _) = chosen.expect("Never fails");
+        track!(self.select(child, loads, segment, path))
+    }
+
+    /// `v`の配下の物理デバイス群のうち、同じセグメント内で未使用の障害ドメインに属するものを一つ選ぶ。
+    ///
+    /// `path`には、`v`から選ばれた物理デバイスまでの経路（`v`自身は除く）が追加される。
+    fn select_by_failure_domain(
+        &self,
+        v: &'a VirtualDevice,
+        level: FailureDomainLevel,
+        loads: &BTreeMap<DeviceId, usize>,
+        segment: &mut SegmentState,
+        path: &mut Vec<&'a DeviceId>,
+    ) -> Result<()> {
+        let mut candidates = Vec::new();
+        for leaf_path in track!(self.allocatable_leaf_paths(&v.id))? {
+            let leaf = *leaf_path.last().expect("Never fails");
+            let key = track!(self.domain_key(leaf, level))?;
+            if !segment.domains.contains(&(level, key.clone())) {
+                let weight = track!(self.weight(leaf.id()))?;
+                candidates.push((leaf.id(), weight, key, leaf_path));
+            }
+        }
+        let (_, _, key, leaf_path) = track_assert_some!(
+            least_loaded(candidates, loads, |c| (c.0, c.1)),
+            ErrorKind::InvalidInput,
+            "Not enough {}s under device {:?} to scatter the segment",
+            level,
+            v.id
+        );
+        segment.domains.insert((level, key));
+        path.extend(leaf_path.into_iter().skip(1).map(|d| d.id()));
+        Ok(())
+    }
+
+    /// `id`から、その配下の割当可能な物理デバイス群までの経路を返す。
+    ///
+    /// 割当不可な稼働状態のデバイスや重みが0のデバイスは、その配下のデバイスごと除外される。
+    fn allocatable_leaf_paths(&self, id: &DeviceId) -> Result<Vec<Vec<&'a Device>>> {
+        let paths = track!(self
+            .graph
+            .leaf_paths(id, |d| Ok(track!(self.weight(d.id()))? > 0)))?;
+        let mut allocatable = Vec::with_capacity(paths.len());
+        for path in paths {
+            // `id`自身が物理デバイスの場合には`leaf_paths`のフィルタが適用されない
+            if path.len() > 1 || track!(self.weight(id))? > 0 {
+                allocatable.push(path);
+            }
+        }
+        Ok(allocatable)
+    }
+
+    fn domain_key(&self, device: &Device, level: FailureDomainLevel) -> Result<String> {
+        let server = device
+            .server()
+            .and_then(|id| self.config.servers.iter().find(|s| s.id == *id));
+        let key = FailureDomain::resolve(device, server).and_then(|d| d.key(level));
+        let key = track_assert_some!(
+            key,
+            ErrorKind::InvalidInput,
+            "No {} is specified for device {:?}",
+            level,
+            device.id()
+        );
+        Ok(key)
+    }
+}
+
+/// セグメント割当のシミュレーション結果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    /// 対象バケツのID。
+    pub bucket: BucketId,
+
+    /// 各セグメントのノード群が割り当てられた物理デバイスのID。
+    ///
+    /// インデックスがセグメント番号に、各要素内のインデックスがセグメント内のノード番号に対応する。
+    pub segments: Vec<Vec<DeviceId>>,
+
+    /// 各デバイスに割り当てられたノードの数。
+    ///
+    /// 仮想デバイスの値は、配下の物理デバイスに割り当てられたノード数の合計となる。
+    pub loads: BTreeMap<DeviceId, usize>,
+}
+impl Placement {
+    /// 指定デバイスに割り当てられたノードの数を返す。
+    pub fn load(&self, device: &DeviceId) -> usize {
+        self.loads.get(device).cloned().unwrap_or(0)
+    }
+
+    /// ノードが割り当てられた物理デバイスの集合を返す。
+    pub fn used_devices(&self) -> BTreeSet<&DeviceId> {
+        self.segments.iter().flatten().collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SegmentState {
+    used: BTreeMap<DeviceId, usize>,
+    domains: BTreeSet<(FailureDomainLevel, String)>,
+}
+
+/// `(割当済みノード数 + 1) / 重み`が最小の候補を返す。
+fn least_loaded<'a, T, F>(candidates: Vec<T>, loads: &BTreeMap<DeviceId, usize>, f: F) -> Option<T>
+where
+    F: Fn(&T) -> (&'a DeviceId, u64),
+{
+    candidates.into_iter().min_by(|a, b| {
+        let (a_id, a_weight) = f(a);
+        let (b_id, b_weight) = f(b);
+        let a_load = loads.get(a_id).cloned().unwrap_or(0) as u128 + 1;
+        let b_load = loads.get(b_id).cloned().unwrap_or(0) as u128 + 1;
+        match (a_load * u128::from(b_weight)).cmp(&(b_load * u128::from(a_weight))) {
+            Ordering::Equal => a_id.cmp(b_id),
+            o => o,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn memory(id: &str, zone: &str, rack: &str, host: &str) -> Value {
+        json!({"memory": {
+            "id": id,
+            "server": "s",
+            "capacity": 100,
+            "failure_domain": {"zone": zone, "rack": rack, "host": host}
+        }})
+    }
+
+    fn virtual_device(id: &str, policy: &str, children: &[&str]) -> Value {
+        json!({"virtual": {"id": id, "policy": policy, "children": children}})
+    }
+
+    fn config(devices: Vec<Value>) -> ClusterConfig {
+        serde_json::from_value(json!({ "devices": devices })).unwrap()
+    }
+
+    fn bucket(tolerable_faults: u32, segment_count: u32) -> Bucket {
+        serde_json::from_value(json!({"replicated": {
+            "id": "b",
+            "device": "root",
+            "segment_count": segment_count,
+            "tolerable_faults": tolerable_faults
+        }}))
+        .unwrap()
+    }
+
+    fn flat(policy: &str, leaves: &[&str]) -> ClusterConfig {
+        let mut devices = vec![virtual_device("root", policy, leaves)];
+        devices.extend(leaves.iter().map(|id| memory(id, "z", "r", id)));
+        config(devices)
+    }
+
+    fn nested(policy: &str) -> ClusterConfig {
+        config(vec![
+            virtual_device("root", policy, &["v1", "v2"]),
+            virtual_device("v1", "NEUTRAL", &["m1", "m2"]),
+            virtual_device("v2", "NEUTRAL", &["m3", "m4"]),
+            memory("m1", "z", "r", "m1"),
+            memory("m2", "z", "r", "m2"),
+            memory("m3", "z", "r", "m3"),
+            memory("m4", "z", "r", "m4"),
+        ])
+    }
+
+    fn ids(segment: &[DeviceId]) -> Vec<&str> {
+        segment.iter().map(|id| id.as_str()).collect()
+    }
+
+    fn distinct(segment: &[DeviceId]) -> usize {
+        segment.iter().collect::<BTreeSet<_>>().len()
+    }
+
+    fn under(segment: &[DeviceId], parent: &[&str]) -> usize {
+        segment
+            .iter()
+            .filter(|id| parent.contains(&id.as_str()))
+            .count()
+    }
+
+    #[test]
+    fn neutral_works() {
+        let config = flat("NEUTRAL", &["m1", "m2"]);
+        let placement = Simulator::new(&config).simulate(&bucket(1, 2)).unwrap();
+        assert_eq!(ids(&placement.segments[0]), ["m1", "m2", "m1"]);
+        assert_eq!(ids(&placement.segments[1]), ["m2", "m1", "m2"]);
+        assert_eq!(placement.load(&"m1".to_owned()), 3);
+        assert_eq!(placement.load(&"root".to_owned()), 6);
+    }
+
+    #[test]
+    fn scatter_if_possible_works() {
+        let config = flat("SCATTER_IF_POSSIBLE", &["m1", "m2", "m3"]);
+        let placement = Simulator::new(&config).simulate(&bucket(1, 3)).unwrap();
+        assert!(placement.segments.iter().all(|s| distinct(s) == 3));
+
+        let config = flat("SCATTER_IF_POSSIBLE", &["m1", "m2"]);
+        let placement = Simulator::new(&config).simulate(&bucket(1, 3)).unwrap();
+        assert!(placement.segments.iter().all(|s| distinct(s) == 2));
+    }
+
+    #[test]
+    fn scatter_works() {
+        let config = flat("SCATTER", &["m1", "m2", "m3"]);
+        let placement = Simulator::new(&config).simulate(&bucket(1, 3)).unwrap();
+        assert!(placement.segments.iter().all(|s| distinct(s) == 3));
+
+        let config = flat("SCATTER", &["m1", "m2"]);
+        assert!(Simulator::new(&config).simulate(&bucket(1, 3)).is_err());
+    }
+
+    #[test]
+    fn gather_works() {
+        let config = nested("GATHER");
+        let placement = Simulator::new(&config).simulate(&bucket(1, 4)).unwrap();
+        for segment in &placement.segments {
+            let v1 = under(segment, &["m1", "m2"]);
+            assert!(v1 == 0 || v1 == 3, "{:?}", segment);
+        }
+        assert_eq!(placement.load(&"v1".to_owned()), 6);
+        assert_eq!(placement.load(&"v2".to_owned()), 6);
+    }
+
+    #[test]
+    fn as_even_as_possible_works() {
+        let config = nested("AS_EVEN_AS_POSSIBLE");
+        let placement = Simulator::new(&config).simulate(&bucket(1, 4)).unwrap();
+        for segment in &placement.segments {
+            let v1 = under(segment, &["m1", "m2"]);
+            assert!(v1 == 1 || v1 == 2, "{:?}", segment);
+        }
+    }
+
+    #[test]
+    fn scatter_by_failure_domain_works() {
+        for &(policy, level) in &[
+            ("SCATTER_BY_ZONE", FailureDomainLevel::Zone),
+            ("SCATTER_BY_RACK", FailureDomainLevel::Rack),
+            ("SCATTER_BY_HOST", FailureDomainLevel::Host),
+        ] {
+            // 対象の階層でのみ、"m1"・"m2"と"m3"と"m4"がそれぞれ別のドメインに属する
+            let domain = |d: &'static str| match level {
+                FailureDomainLevel::Zone => (d, "r", "h"),
+                FailureDomainLevel::Rack => ("z", d, "h"),
+                FailureDomainLevel::Host => ("z", "r", d),
+            };
+            let leaf = |id: &str, d: &'static str| {
+                let (zone, rack, host) = domain(d);
+                memory(id, zone, rack, host)
+            };
+            let mut config = config(vec![
+                virtual_device("root", policy, &["v1", "v2"]),
+                virtual_device("v1", "NEUTRAL", &["m1", "m2"]),
+                virtual_device("v2", "NEUTRAL", &["m3", "m4"]),
+                leaf("m1", "d1"),
+                leaf("m2", "d1"),
+                leaf("m3", "d2"),
+                leaf("m4", "d3"),
+            ]);
+            let placement = Simulator::new(&config).simulate(&bucket(0, 4)).unwrap();
+            // 中間の仮想デバイスも経路に含まれる
+            assert_eq!(placement.load(&"v1".to_owned()), 2, "{}", policy);
+            assert_eq!(placement.load(&"v2".to_owned()), 2, "{}", policy);
+
+            // 同じセグメント内で同じドメインは使用されない
+            let placement = Simulator::new(&config).simulate(&bucket(1, 4)).unwrap();
+            for segment in &placement.segments {
+                assert_eq!(ids(segment).len(), 3);
+                assert_eq!(
+                    under(segment, &["m1", "m2"]),
+                    1,
+                    "{}: {:?}",
+                    policy,
+                    segment
+                );
+            }
+
+            // 割当不可な中間デバイスの配下は候補にならない
+            if let Device::Virtual(ref mut v) = config.devices[2] {
+                v.state = crate::entity::device::DeviceState::Draining;
+            }
+            assert!(
+                Simulator::new(&config).simulate(&bucket(1, 4)).is_err(),
+                "{}",
+                policy
+            );
+        }
+    }
+
+    #[test]
+    fn non_allocatable_ancestors_are_excluded() {
+        let mut config = nested("NEUTRAL");
+        if let Device::Virtual(ref mut v) = config.devices[2] {
+            v.state = crate::entity::device::DeviceState::Draining;
+        }
+        let mut bucket = bucket(0, 0);
+        let placement = Simulator::new(&config).simulate(&bucket).unwrap();
+        // 割当可能な物理デバイスは"m1"と"m2"の二つのみ
+        assert_eq!(placement.segments.len(), 20);
+        assert_eq!(placement.load(&"v2".to_owned()), 0);
+
+        bucket.set_segment_count(1);
+        if let Device::Virtual(ref mut v) = config.devices[1] {
+            v.weight = crate::entity::device::Weight::Absolute(0);
+        }
+        assert!(Simulator::new(&config).simulate(&bucket).is_err());
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let config = config(vec![
+            virtual_device("root", "NEUTRAL", &["v1"]),
+            virtual_device("v1", "NEUTRAL", &["v2", "m1"]),
+            virtual_device("v2", "NEUTRAL", &["v1"]),
+            memory("m1", "z", "r", "m1"),
+        ]);
+        let simulator = Simulator::new(&config);
+        let is_invalid = |r: Result<_>| r.err().map(|e| *e.kind()) == Some(ErrorKind::InvalidInput);
+        assert!(is_invalid(simulator.weight(&"root".to_owned()).map(|_| ())));
+        assert!(is_invalid(simulator.capacity(&"v1".to_owned()).map(|_| ())));
+        assert!(is_invalid(simulator.simulate(&bucket(1, 1)).map(|_| ())));
+
+        // 循環とは無関係なデバイスであっても、木構造全体が不正なためエラーとなる
+        assert!(is_invalid(simulator.weight(&"m1".to_owned()).map(|_| ())));
+    }
+}